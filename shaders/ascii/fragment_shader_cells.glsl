@@ -0,0 +1,82 @@
+#version 330 core
+
+// One fragment per cell, rows top-down: color of the cell and its luminance in alpha
+
+uniform sampler2D img_texture;
+uniform vec2 resolution;
+uniform vec2 cell_size;
+uniform float cell_average; // 0 mean, 1 median (by luminance), 2 dominant color
+
+out vec4 FragColor;
+
+const int MAX_SAMPLES = 8; // per axis
+
+float luminance(vec3 c) {
+    return dot(c, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec2 cell = floor(gl_FragCoord.xy);
+    ivec2 n = clamp(ivec2(ceil(cell_size)), ivec2(1), ivec2(MAX_SAMPLES));
+
+    // Mip level that covers the distance between samples, for sources larger than the window
+    vec2 texels_per_sample = vec2(textureSize(img_texture, 0)) / resolution * cell_size / vec2(n);
+    float lod = max(0.0, log2(max(texels_per_sample.x, texels_per_sample.y)));
+
+    vec3 samples[MAX_SAMPLES * MAX_SAMPLES];
+    int count = n.x * n.y;
+    vec3 sum = vec3(0.0);
+
+    for (int y = 0; y < n.y; y++) {
+        for (int x = 0; x < n.x; x++) {
+            vec2 pixel = cell * cell_size + (vec2(x, y) + 0.5) / vec2(n) * cell_size;
+            vec3 c = textureLod(img_texture, pixel / resolution, lod).rgb;
+
+            samples[y * n.x + x] = c;
+            sum += c;
+        }
+    }
+
+    vec3 color = sum / float(count);
+    int mode = int(cell_average + 0.5);
+
+    if (mode == 1) {
+        // Sample with as many darker as brighter samples
+        for (int i = 0; i < count; i++) {
+            float l = luminance(samples[i]);
+            int below = 0;
+
+            for (int j = 0; j < count; j++) {
+                float lj = luminance(samples[j]);
+                below += (lj < l || (lj == l && j < i)) ? 1 : 0;
+            }
+
+            if (below == count / 2) {
+                color = samples[i];
+            }
+        }
+    } else if (mode == 2) {
+        // Mean of the most common color, quantized to 8 levels per channel
+        int best = -1;
+
+        for (int i = 0; i < count; i++) {
+            ivec3 bin = ivec3(samples[i] * 7.999);
+            int matches = 0;
+            vec3 bin_sum = vec3(0.0);
+
+            for (int j = 0; j < count; j++) {
+                if (ivec3(samples[j] * 7.999) == bin) {
+                    matches++;
+                    bin_sum += samples[j];
+                }
+            }
+
+            if (matches > best) {
+                best = matches;
+                color = bin_sum / float(matches);
+            }
+        }
+    }
+
+    FragColor = vec4(color, luminance(color));
+}
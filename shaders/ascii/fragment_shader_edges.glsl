@@ -0,0 +1,65 @@
+#version 330 core
+
+// One fragment per cell: edge state, gradient direction and magnitude of the cell luminance
+
+uniform sampler2D cell_texture; // luminance in alpha
+uniform sampler2D previous_edges; // output of the last frame
+uniform float edge_detector; // 0 Sobel, 1 Scharr, 2 difference of Gaussians
+uniform float edge_threshold;
+uniform float edge_hysteresis; // an edge of the last frame stays while above this fraction of the threshold
+
+out vec4 FragColor;
+
+float cell_luminance(ivec2 cell) {
+    return texelFetch(cell_texture, clamp(cell, ivec2(0), textureSize(cell_texture, 0) - 1), 0).a;
+}
+
+void main() {
+    ivec2 cell = ivec2(gl_FragCoord.xy);
+    int detector = int(edge_detector + 0.5);
+
+    float tl = cell_luminance(cell + ivec2(-1, 1));
+    float t = cell_luminance(cell + ivec2(0, 1));
+    float tr = cell_luminance(cell + ivec2(1, 1));
+
+    float l = cell_luminance(cell + ivec2(-1, 0));
+    float r = cell_luminance(cell + ivec2(1, 0));
+
+    float bl = cell_luminance(cell + ivec2(-1, -1));
+    float b = cell_luminance(cell + ivec2(0, -1));
+    float br = cell_luminance(cell + ivec2(1, -1));
+
+    // Corner and side weights, Scharr is scaled to the magnitude of Sobel
+    vec2 w = detector == 1 ? vec2(3.0, 10.0) / 4.0 : vec2(1.0, 2.0);
+
+    float sx = -w.x*tl - w.y*l - w.x*bl +
+                w.x*tr + w.y*r + w.x*br;
+
+    float sy = -w.x*tl - w.y*t - w.x*tr +
+                w.x*bl + w.y*b + w.x*br;
+
+    vec2 grad = vec2(sx, sy);
+    float mag = length(grad);
+
+    if (detector == 2) {
+        // Band-pass response of a 5x5 neighbourhood (sigma 1 minus sigma 1.6), the direction still comes from Sobel
+        float dog = 0.0;
+
+        for (int y = -2; y <= 2; y++) {
+            for (int x = -2; x <= 2; x++) {
+                float d2 = float(x * x + y * y);
+                float g = exp(-d2 / 2.0) / 6.2832 - exp(-d2 / 5.12) / 16.085;
+                dog += g * cell_luminance(cell + ivec2(x, y));
+            }
+        }
+
+        mag = abs(dog) * 20.0;
+    }
+
+    bool was_edge = texelFetch(previous_edges, cell, 0).r > 0.5;
+    bool edge = mag >= edge_threshold || (was_edge && mag >= edge_threshold * edge_hysteresis);
+
+    vec2 dir = mag > 0.0 && length(grad) > 0.0 ? normalize(grad) : vec2(0.0);
+
+    FragColor = vec4(edge ? 1.0 : 0.0, dir, mag);
+}
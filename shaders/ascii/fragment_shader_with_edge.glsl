@@ -4,75 +4,37 @@ uniform sampler2D font_texture;
 uniform ivec2 font_grid;
 uniform int glyph_count;
 
-uniform sampler2D img_texture;
+uniform sampler2D cell_texture; // color and luminance of each cell, from fragment_shader_cells.glsl
+uniform sampler2D edge_texture; // edge state and direction of each cell, from fragment_shader_edges.glsl
 uniform vec2 resolution;
 uniform vec2 cell_size;
 
-uniform float edge_threshold;
-
 in vec2 TexPos;
 
 out vec4 FragColor;
 
-float luminance(vec3 c) {
-    return dot(c, vec3(0.299, 0.587, 0.114));
-}
-
-float sample_cell_luminance(vec2 base_cell, vec2 offset) {
-    vec2 c = base_cell + offset;
-    vec2 uv = (c * cell_size + cell_size * 0.5) / resolution;
-
-    return luminance(texture(img_texture, uv).rgb);
-}
-
 void main() {
     vec2 pixel = TexPos * resolution;
-    vec2 cell = floor(pixel / cell_size);
-
-    // Read average color from a cell in the image
-    vec2 cell_uv = (cell * cell_size + cell_size * 0.5) / resolution;
-    vec3 avg_col = texture(img_texture, cell_uv).rgb;
+    ivec2 cell = ivec2(floor(pixel / cell_size));
 
-    // Sobel on 3x3 kernel
-    float tl = sample_cell_luminance(cell, vec2(-1, 1));
-    float t = sample_cell_luminance(cell, vec2(0, 1));
-    float tr = sample_cell_luminance(cell, vec2(1, 1));
-
-    float l = sample_cell_luminance(cell, vec2(-1, 0));
-    float c = sample_cell_luminance(cell, vec2(0, 0));
-    float r = sample_cell_luminance(cell, vec2(1, 0));
-
-    float bl = sample_cell_luminance(cell, vec2(-1, -1));
-    float b = sample_cell_luminance(cell, vec2(0, -1));
-    float br = sample_cell_luminance(cell, vec2(1, -1));
-
-    // Sobel X
-    float sx = -tl - 2.0*l - bl +
-                tr + 2.0*r + br;
-
-    // Sobel Y
-    float sy = -tl - 2.0*t - tr +
-                bl + 2.0*b + br;
-
-    vec2 grad = vec2(sx, sy);
-    float mag = length(grad); // Strength of the edge (0 = no edge, higher = stronger edge)
+    vec4 cell_col = texelFetch(cell_texture, cell, 0);
+    vec4 edge = texelFetch(edge_texture, cell, 0);
 
     // Choose correct glyph (fill or edge)
     int glyph_index;
 
-    if (mag < edge_threshold) {
+    if (edge.r < 0.5) {
         // Map brightness to glyph index (non-edge characters)
         // -4 because last 4 glyphs area reserved for edge characters (— / \ |)
-        glyph_index = int(c * float(glyph_count - 1 - 4));
+        glyph_index = int(cell_col.a * float(glyph_count - 1 - 4));
         glyph_index = clamp(glyph_index, 0, glyph_count - 1 - 4);
 
     } else {
         // 4-direction ASCII edges based normalized vector comparison
-        // Sobel returns the GRADIENT direction which is PERPENDICULAR to the actual edge
-        // Normalize gradient so its length = 1
+        // The stored direction is the GRADIENT direction which is PERPENDICULAR to the actual edge
         //   |g.x| - how horizontal the gradient is
         //   |g.y| - how vertical the gradient is
-        vec2 g = normalize(grad);
+        vec2 g = edge.gb;
         float agx = abs(g.x);
         float agy = abs(g.y);
 
@@ -98,5 +60,5 @@ void main() {
     vec2 font_uv = glyph_uv_origin + local_uv * glyph_size;
     float glyph_alpha = texture(font_texture, font_uv).a;
 
-    FragColor = vec4(avg_col * glyph_alpha, 1.0);
-}
\ No newline at end of file
+    FragColor = vec4(cell_col.rgb * glyph_alpha, 1.0);
+}
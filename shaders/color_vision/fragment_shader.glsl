@@ -0,0 +1,106 @@
+#version 330 core
+
+uniform sampler2D img_texture;
+
+uniform float cvd_mode; // 0 simulate, 1 daltonize, 2 high contrast, 3 inverted luminance
+uniform float cvd_type; // 0 protan, 1 deutan, 2 tritan
+uniform float severity;
+
+in vec2 TexPos;
+
+out vec4 FragColor;
+
+// Machado et al. 2009 simulation matrices in linear RGB at severity 0.5 and 1.0 (rows as written in the paper, so colors multiply from the left)
+const mat3 PROTAN_05 = mat3(
+    0.458064, 0.679578, -0.137642,
+    0.092785, 0.846313, 0.060902,
+    -0.007494, -0.016807, 1.024301
+);
+
+const mat3 PROTAN_10 = mat3(
+    0.152286, 1.052583, -0.204868,
+    0.114503, 0.786281, 0.099216,
+    -0.003882, -0.048116, 1.051998
+);
+
+const mat3 DEUTAN_05 = mat3(
+    0.547494, 0.607765, -0.155259,
+    0.181692, 0.781742, 0.036566,
+    -0.010410, 0.027275, 0.983136
+);
+
+const mat3 DEUTAN_10 = mat3(
+    0.367322, 0.860646, -0.227968,
+    0.280085, 0.672501, 0.047413,
+    -0.011820, 0.042940, 0.968881
+);
+
+const mat3 TRITAN_05 = mat3(
+    1.017277, 0.027029, -0.044306,
+    -0.006113, 0.958479, 0.047634,
+    0.006379, 0.248708, 0.744913
+);
+
+const mat3 TRITAN_10 = mat3(
+    1.255528, -0.076749, -0.178779,
+    -0.078411, 0.930809, 0.147602,
+    0.004733, 0.691367, 0.303900
+);
+
+vec3 to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 to_srgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+float luminance(vec3 c) {
+    return dot(c, vec3(0.2126, 0.7152, 0.0722));
+}
+
+// Piecewise linear between the tabulated severities
+vec3 simulate(vec3 lin, int type, float s) {
+    mat3 half_matrix = type == 0 ? PROTAN_05 : type == 1 ? DEUTAN_05 : TRITAN_05;
+    mat3 full_matrix = type == 0 ? PROTAN_10 : type == 1 ? DEUTAN_10 : TRITAN_10;
+
+    vec3 half_result = lin * half_matrix;
+
+    if (s <= 0.5) {
+        return mix(lin, half_result, s * 2.0);
+    }
+
+    return mix(half_result, lin * full_matrix, s * 2.0 - 1.0);
+}
+
+void main() {
+    vec3 color = texture(img_texture, TexPos).rgb;
+    vec3 lin = to_linear(color);
+
+    int mode = int(cvd_mode + 0.5);
+    int type = int(cvd_type + 0.5);
+    float s = clamp(severity, 0.0, 1.0);
+
+    if (mode == 0) {
+        lin = simulate(lin, type, s);
+    } else if (mode == 1) {
+        // Fidaner daltonization: the information lost by the deficiency is moved to the channels that are still seen
+        vec3 error = lin - simulate(lin, type, s);
+        vec3 shift = type == 2
+            ? vec3(error.r + 0.7 * error.b, error.g + 0.7 * error.b, 0.0)
+            : vec3(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+
+        lin += shift;
+    } else if (mode == 2) {
+        // Steeper curve around middle gray in sRGB, severity is the strength
+        vec3 contrast = clamp((color - 0.5) * (1.0 + 3.0 * s) + 0.5, 0.0, 1.0);
+        lin = to_linear(contrast);
+    } else {
+        // Dark becomes bright with the hue kept
+        float l = luminance(lin);
+        vec3 inverted = lin + (1.0 - l) - l;
+        lin = mix(lin, inverted, s);
+    }
+
+    FragColor = vec4(to_srgb(clamp(lin, 0.0, 1.0)), 1.0);
+}
@@ -0,0 +1,96 @@
+#version 330 core
+
+uniform sampler2D img_texture;
+uniform vec2 resolution; // framebuffer size
+uniform vec2 grid_origin; // framebuffer position of the window on the screen, like the pixel effect
+uniform float pixel_scale; // framebuffer pixels per window pixel
+uniform float dpi; // window pixels per inch
+
+uniform float halftone_mode; // 0 CMYK, 1 newspaper
+uniform float dot_shape; // 0 round, 1 line, 2 square
+uniform float lpi;
+uniform float angle_c; // screen angles of the plates in degrees
+uniform float angle_m;
+uniform float angle_y;
+uniform float angle_k;
+uniform float paper_tint;
+
+in vec2 TexPos;
+
+out vec4 FragColor;
+
+const vec3 PAPER = vec3(0.95, 0.92, 0.84); // newsprint at full tint
+
+const vec3 INK_C = vec3(0.0, 0.63, 0.89);
+const vec3 INK_M = vec3(0.89, 0.0, 0.49);
+const vec3 INK_Y = vec3(1.0, 0.93, 0.0);
+const vec3 INK_K = vec3(0.1, 0.1, 0.12);
+
+mat2 rotation(float degrees) {
+    float a = radians(degrees);
+    return mat2(cos(a), sin(a), -sin(a), cos(a));
+}
+
+vec4 cmyk(vec3 c) {
+    float k = 1.0 - max(c.r, max(c.g, c.b));
+    vec3 cmy = k < 1.0 ? (1.0 - c - k) / (1.0 - k) : vec3(0.0);
+
+    return vec4(cmy, k);
+}
+
+// Coverage of a plate at this fragment: the cell of the rotated screen is found, its color read at the center and turned into a dot
+float plate(vec2 screen, float cell_px, float angle, int channel, bool mono) {
+    mat2 r = rotation(angle);
+    vec2 q = r * screen / cell_px;
+    vec2 cell = floor(q) + 0.5;
+    vec2 local = q - cell; // -0.5..0.5 inside the cell
+
+    // Mean of a few samples around the cell center
+    vec3 color = vec3(0.0);
+
+    for (int y = -1; y <= 1; y++) {
+        for (int x = -1; x <= 1; x++) {
+            vec2 center = transpose(r) * ((cell + vec2(x, y) / 3.0) * cell_px);
+            color += texture(img_texture, clamp((center - grid_origin) / resolution, 0.0, 1.0)).rgb;
+        }
+    }
+
+    color /= 9.0;
+
+    float value = mono ? 1.0 - dot(color, vec3(0.299, 0.587, 0.114)) : cmyk(color)[channel];
+
+    // Signed distance to the dot edge in cell units, the dot area matches the value
+    int shape = int(dot_shape + 0.5);
+    float dist;
+
+    if (shape == 1) {
+        dist = abs(local.y) - value * 0.5;
+    } else if (shape == 2) {
+        dist = max(abs(local.x), abs(local.y)) - sqrt(value) * 0.5;
+    } else {
+        dist = length(local) - sqrt(value / 3.14159265);
+    }
+
+    // Antialiased over one framebuffer pixel
+    return clamp(0.5 - dist * cell_px, 0.0, 1.0);
+}
+
+void main() {
+    vec2 screen = grid_origin + TexPos * resolution;
+    float cell_px = max(2.0, dpi * pixel_scale / max(lpi, 1.0));
+
+    vec3 paper = mix(vec3(1.0), PAPER, paper_tint);
+    vec3 color = paper;
+
+    if (int(halftone_mode + 0.5) == 1) {
+        color *= mix(vec3(1.0), INK_K, plate(screen, cell_px, angle_k, 3, true));
+    } else {
+        // Inks multiply like transparent layers on the paper
+        color *= mix(vec3(1.0), INK_C, plate(screen, cell_px, angle_c, 0, false));
+        color *= mix(vec3(1.0), INK_M, plate(screen, cell_px, angle_m, 1, false));
+        color *= mix(vec3(1.0), INK_Y, plate(screen, cell_px, angle_y, 2, false));
+        color *= mix(vec3(1.0), INK_K, plate(screen, cell_px, angle_k, 3, false));
+    }
+
+    FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,11 @@
+#version 330 core
+
+layout (location = 0) in vec2 pos;
+layout (location = 1) in vec2 aTexPos;
+
+out vec2 TexPos;
+
+void main() {
+    gl_Position = vec4(pos, 0.0, 1.0);
+    TexPos = aTexPos;
+}
\ No newline at end of file
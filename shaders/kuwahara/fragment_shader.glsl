@@ -0,0 +1,163 @@
+#version 330 core
+
+uniform sampler2D img_texture;
+uniform sampler2D tensor_texture; // smoothed structure tensor at the framebuffer position, anisotropic mode only
+uniform vec2 resolution;
+
+uniform float kuwahara_mode; // 0 basic, 1 generalized, 2 anisotropic
+uniform float kernel_radius;
+uniform float sectors;
+uniform float sharpness;
+
+in vec2 TexPos;
+
+out vec4 FragColor;
+
+const int MAX_SECTORS = 12;
+const float PI = 3.14159265;
+
+vec3 sample_offset(vec2 offset) {
+    return texture(img_texture, TexPos + offset / resolution).rgb;
+}
+
+// Mean of the square quadrant with the lowest variance
+vec3 basic(int radius) {
+    vec3 mean[4] = vec3[4](vec3(0.0), vec3(0.0), vec3(0.0), vec3(0.0));
+    vec3 square[4] = vec3[4](vec3(0.0), vec3(0.0), vec3(0.0), vec3(0.0));
+
+    for (int y = -radius; y <= radius; y++) {
+        for (int x = -radius; x <= radius; x++) {
+            vec3 c = sample_offset(vec2(x, y));
+
+            // Samples on the middle row and column belong to both quadrants
+            for (int q = 0; q < 4; q++) {
+                int sx = (q & 1) == 0 ? -1 : 1;
+                int sy = q < 2 ? -1 : 1;
+
+                if (x * sx >= 0 && y * sy >= 0) {
+                    mean[q] += c;
+                    square[q] += c * c;
+                }
+            }
+        }
+    }
+
+    float count = float((radius + 1) * (radius + 1));
+    vec3 result = vec3(0.0);
+    float lowest = 1e9;
+
+    for (int q = 0; q < 4; q++) {
+        vec3 m = mean[q] / count;
+        vec3 v = abs(square[q] / count - m * m);
+        float variance = v.r + v.g + v.b;
+
+        if (variance < lowest) {
+            lowest = variance;
+            result = m;
+        }
+    }
+
+    return result;
+}
+
+// Overlapping sectors of an ellipse with Gaussian falloff, each weighted by how flat it is. A circle (anisotropy 0) is the generalized filter
+vec3 sectors_filter(int radius, int n, float angle, float anisotropy) {
+    vec3 mean[MAX_SECTORS];
+    vec3 square[MAX_SECTORS];
+    float weight[MAX_SECTORS];
+
+    for (int k = 0; k < n; k++) {
+        mean[k] = vec3(0.0);
+        square[k] = vec3(0.0);
+        weight[k] = 0.0;
+    }
+
+    // Ellipse stretched along the edge, a and b are its half axes in pixels
+    float r = float(radius);
+    float a = r * (1.0 + anisotropy);
+    float b = r / (1.0 + anisotropy);
+    float cs = cos(angle);
+    float sn = sin(angle);
+
+    int extent_x = int(ceil(sqrt(a * a * cs * cs + b * b * sn * sn)));
+    int extent_y = int(ceil(sqrt(a * a * sn * sn + b * b * cs * cs)));
+
+    for (int y = -extent_y; y <= extent_y; y++) {
+        for (int x = -extent_x; x <= extent_x; x++) {
+            // Position in the unit disk of the ellipse
+            vec2 d = vec2(x, y);
+            vec2 v = vec2(cs * d.x + sn * d.y, -sn * d.x + cs * d.y) / vec2(a, b);
+            float dist2 = dot(v, v);
+
+            if (dist2 > 1.0) {
+                continue;
+            }
+
+            vec3 c = sample_offset(d);
+            float falloff = exp(-2.0 * dist2);
+            float phi = atan(v.y, v.x);
+
+            for (int k = 0; k < n; k++) {
+                // Angle to the middle of the sector, sectors overlap their neighbours by half
+                float delta = mod(phi - 2.0 * PI * float(k) / float(n) + PI, 2.0 * PI) - PI;
+                float w = dist2 == 0.0 ? 1.0 : pow(max(0.0, cos(delta * float(n) / 4.0)), 2.0);
+                w *= falloff;
+
+                mean[k] += w * c;
+                square[k] += w * c * c;
+                weight[k] += w;
+            }
+        }
+    }
+
+    vec3 sum = vec3(0.0);
+    float weight_sum = 0.0;
+
+    for (int k = 0; k < n; k++) {
+        if (weight[k] <= 0.0) {
+            continue;
+        }
+
+        vec3 m = mean[k] / weight[k];
+        vec3 v = abs(square[k] / weight[k] - m * m);
+
+        // Sharpness is the exponent: higher values follow the flattest sector more closely
+        float w = 1.0 / (1.0 + pow(255.0 * sqrt(v.r + v.g + v.b), sharpness));
+        sum += w * m;
+        weight_sum += w;
+    }
+
+    return weight_sum > 0.0 ? sum / weight_sum : sample_offset(vec2(0.0));
+}
+
+void main() {
+    int mode = int(kuwahara_mode + 0.5);
+    int radius = max(1, int(kernel_radius + 0.5));
+    int n = clamp(int(sectors + 0.5), 3, MAX_SECTORS);
+
+    vec3 color;
+
+    if (mode == 0) {
+        color = basic(radius);
+    } else if (mode == 1) {
+        color = sectors_filter(radius, n, 0.0, 0.0);
+    } else {
+        // Edge direction and anisotropy from the eigenvalues of the tensor
+        vec3 t = texelFetch(tensor_texture, ivec2(gl_FragCoord.xy), 0).xyz;
+        float e = t.x;
+        float f = t.y;
+        float g = t.z;
+
+        float root = sqrt((e - g) * (e - g) + 4.0 * f * f);
+        float lambda1 = 0.5 * (e + g + root);
+        float lambda2 = 0.5 * (e + g - root);
+
+        vec2 direction = vec2(lambda1 - e, -f);
+        direction = length(direction) > 0.0 ? normalize(direction) : vec2(0.0, 1.0);
+
+        float anisotropy = lambda1 + lambda2 > 0.0 ? (lambda1 - lambda2) / (lambda1 + lambda2) : 0.0;
+        color = sectors_filter(radius, n, atan(direction.y, direction.x), anisotropy);
+    }
+
+    FragColor = vec4(color, 1.0);
+}
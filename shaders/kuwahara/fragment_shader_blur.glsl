@@ -0,0 +1,27 @@
+#version 330 core
+
+// One direction of a separable Gaussian, read and written at the same framebuffer position
+
+uniform sampler2D tensor_texture;
+uniform ivec2 direction; // (1, 0) or (0, 1)
+
+out vec4 FragColor;
+
+const float SIGMA = 2.0;
+const int RADIUS = 6;
+
+void main() {
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    ivec2 size = textureSize(tensor_texture, 0);
+
+    vec4 sum = vec4(0.0);
+    float weight_sum = 0.0;
+
+    for (int i = -RADIUS; i <= RADIUS; i++) {
+        float weight = exp(-float(i * i) / (2.0 * SIGMA * SIGMA));
+        sum += weight * texelFetch(tensor_texture, clamp(pixel + direction * i, ivec2(0), size - 1), 0);
+        weight_sum += weight;
+    }
+
+    FragColor = sum / weight_sum;
+}
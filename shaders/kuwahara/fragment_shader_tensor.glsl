@@ -0,0 +1,29 @@
+#version 330 core
+
+// Structure tensor of the source: (E, F, G) = (gx*gx, gx*gy, gy*gy) summed over the color channels
+
+uniform sampler2D img_texture;
+uniform vec2 resolution;
+
+in vec2 TexPos;
+
+out vec4 FragColor;
+
+vec3 sample_offset(float x, float y) {
+    return texture(img_texture, TexPos + vec2(x, y) / resolution).rgb;
+}
+
+void main() {
+    // Sobel of each channel, scaled to a unit step
+    vec3 gx = (
+        -1.0 * sample_offset(-1.0, -1.0) - 2.0 * sample_offset(-1.0, 0.0) - 1.0 * sample_offset(-1.0, 1.0) +
+         1.0 * sample_offset(1.0, -1.0) + 2.0 * sample_offset(1.0, 0.0) + 1.0 * sample_offset(1.0, 1.0)
+    ) / 4.0;
+
+    vec3 gy = (
+        -1.0 * sample_offset(-1.0, -1.0) - 2.0 * sample_offset(0.0, -1.0) - 1.0 * sample_offset(1.0, -1.0) +
+         1.0 * sample_offset(-1.0, 1.0) + 2.0 * sample_offset(0.0, 1.0) + 1.0 * sample_offset(1.0, 1.0)
+    ) / 4.0;
+
+    FragColor = vec4(dot(gx, gx), dot(gx, gy), dot(gy, gy), 1.0);
+}
@@ -0,0 +1,90 @@
+#version 330 core
+
+uniform sampler2D img_texture; // effect output, same size as the window
+uniform sampler3D lut_3d;
+uniform sampler2D lut_1d; // size x 1
+uniform bool is_3d;
+uniform float lut_size;
+uniform vec3 domain_min;
+uniform vec3 domain_max;
+uniform float lut_strength; // 0 original, 1 fully graded
+uniform float lut_tetrahedral; // 0 trilinear, 1 tetrahedral interpolation
+
+out vec4 FragColor;
+
+// Texture coordinate of the center of an entry
+vec3 entry_uv(vec3 t) {
+    return (t * (lut_size - 1.0) + 0.5) / lut_size;
+}
+
+vec3 lookup_trilinear(vec3 t) {
+    return texture(lut_3d, entry_uv(t)).rgb;
+}
+
+// Interpolates between the 4 corners of the tetrahedron the color is in, avoids the hue shifts of trilinear on the gray axis
+vec3 lookup_tetrahedral(vec3 t) {
+    vec3 p = t * (lut_size - 1.0);
+    ivec3 base = ivec3(min(floor(p), vec3(lut_size - 2.0)));
+    vec3 f = p - vec3(base);
+
+    vec3 c000 = texelFetch(lut_3d, base, 0).rgb;
+    vec3 c111 = texelFetch(lut_3d, base + ivec3(1, 1, 1), 0).rgb;
+
+    if (f.r >= f.g) {
+        if (f.g >= f.b) {
+            vec3 c100 = texelFetch(lut_3d, base + ivec3(1, 0, 0), 0).rgb;
+            vec3 c110 = texelFetch(lut_3d, base + ivec3(1, 1, 0), 0).rgb;
+            return c000 + f.r * (c100 - c000) + f.g * (c110 - c100) + f.b * (c111 - c110);
+        } else if (f.r >= f.b) {
+            vec3 c100 = texelFetch(lut_3d, base + ivec3(1, 0, 0), 0).rgb;
+            vec3 c101 = texelFetch(lut_3d, base + ivec3(1, 0, 1), 0).rgb;
+            return c000 + f.r * (c100 - c000) + f.b * (c101 - c100) + f.g * (c111 - c101);
+        } else {
+            vec3 c001 = texelFetch(lut_3d, base + ivec3(0, 0, 1), 0).rgb;
+            vec3 c101 = texelFetch(lut_3d, base + ivec3(1, 0, 1), 0).rgb;
+            return c000 + f.b * (c001 - c000) + f.r * (c101 - c001) + f.g * (c111 - c101);
+        }
+    } else {
+        if (f.b >= f.g) {
+            vec3 c001 = texelFetch(lut_3d, base + ivec3(0, 0, 1), 0).rgb;
+            vec3 c011 = texelFetch(lut_3d, base + ivec3(0, 1, 1), 0).rgb;
+            return c000 + f.b * (c001 - c000) + f.g * (c011 - c001) + f.r * (c111 - c011);
+        } else if (f.b >= f.r) {
+            vec3 c010 = texelFetch(lut_3d, base + ivec3(0, 1, 0), 0).rgb;
+            vec3 c011 = texelFetch(lut_3d, base + ivec3(0, 1, 1), 0).rgb;
+            return c000 + f.g * (c010 - c000) + f.b * (c011 - c010) + f.r * (c111 - c011);
+        } else {
+            vec3 c010 = texelFetch(lut_3d, base + ivec3(0, 1, 0), 0).rgb;
+            vec3 c110 = texelFetch(lut_3d, base + ivec3(1, 1, 0), 0).rgb;
+            return c000 + f.g * (c010 - c000) + f.r * (c110 - c010) + f.b * (c111 - c110);
+        }
+    }
+}
+
+// Each channel through its own curve
+vec3 lookup_1d(vec3 t) {
+    vec3 uv = entry_uv(t);
+
+    return vec3(
+        texture(lut_1d, vec2(uv.r, 0.5)).r,
+        texture(lut_1d, vec2(uv.g, 0.5)).g,
+        texture(lut_1d, vec2(uv.b, 0.5)).b
+    );
+}
+
+void main() {
+    vec4 col = texelFetch(img_texture, ivec2(gl_FragCoord.xy), 0);
+    vec3 t = clamp((col.rgb - domain_min) / (domain_max - domain_min), 0.0, 1.0);
+
+    vec3 graded;
+
+    if (!is_3d) {
+        graded = lookup_1d(t);
+    } else if (lut_tetrahedral > 0.5) {
+        graded = lookup_tetrahedral(t);
+    } else {
+        graded = lookup_trilinear(t);
+    }
+
+    FragColor = vec4(mix(col.rgb, graded, lut_strength), col.a);
+}
@@ -0,0 +1,58 @@
+#version 330 core
+
+// Lens around a point of the framebuffer, everything else is copied unchanged
+
+uniform sampler2D img_texture;
+uniform vec2 lens_center; // framebuffer pixels, origin at the bottom left like gl_FragCoord
+uniform float pixel_scale; // framebuffer pixels per window pixel
+
+uniform float lens_zoom;
+uniform float lens_radius; // window pixels
+uniform float lens_smooth; // 0 nearest, 1 linear sampling
+uniform float lens_grid; // 1 draws a pixel grid and a crosshair
+
+out vec4 FragColor;
+
+void main() {
+    ivec2 size = textureSize(img_texture, 0);
+    vec2 d = gl_FragCoord.xy - lens_center;
+    float radius = lens_radius * pixel_scale;
+    float dist = length(d);
+
+    if (dist > radius + 2.0 * pixel_scale) {
+        FragColor = texelFetch(img_texture, ivec2(gl_FragCoord.xy), 0);
+        return;
+    }
+
+    // Dark ring around the lens
+    if (dist > radius) {
+        FragColor = vec4(0.1, 0.1, 0.1, 1.0);
+        return;
+    }
+
+    float zoom = max(lens_zoom, 1.0);
+    vec2 source = lens_center + d / zoom;
+    vec3 color;
+
+    if (lens_smooth > 0.5) {
+        color = texture(img_texture, source / vec2(size)).rgb;
+    } else {
+        color = texelFetch(img_texture, clamp(ivec2(floor(source)), ivec2(0), size - 1), 0).rgb;
+    }
+
+    if (lens_grid > 0.5) {
+        // Grid between source pixels once they are large enough to tell apart
+        vec2 edge = fract(source);
+
+        if (zoom >= 4.0 && (edge.x < 1.0 / zoom || edge.y < 1.0 / zoom)) {
+            color *= 0.6;
+        }
+
+        // Crosshair through the center, with a gap around the pixel under it
+        if ((abs(d.x) < pixel_scale || abs(d.y) < pixel_scale) && dist > zoom * 1.5) {
+            color = 1.0 - color;
+        }
+    }
+
+    FragColor = vec4(color, 1.0);
+}
@@ -1,20 +1,82 @@
 #version 330 core
 
 uniform sampler2D img_texture;
-uniform vec2 resolution;
-uniform vec2 cell_size;
+uniform vec2 resolution; // framebuffer size
+uniform vec2 grid_origin; // framebuffer position of the window on the screen, so the grid stays put when the window moves
+uniform float pixel_scale; // framebuffer pixels per window pixel
+
+uniform float pixel_size; // in window pixels
+uniform float outline; // darkening of cells on the dark side of an edge
+uniform float outline_threshold; // luminance difference that counts as an edge
+
+uniform vec3 palette[32];
+uniform int palette_size; // 0 keeps the cell colors
 
 in vec2 TexPos;
 
 out vec4 FragColor;
 
+const int MAX_SAMPLES = 8; // per axis
+
+float luminance(vec3 c) {
+    return dot(c, vec3(0.299, 0.587, 0.114));
+}
+
+// Mean of up to n x n samples of a cell given in screen (top-down framebuffer) pixels, clipped to the window
+vec3 cell_color(vec2 cell, vec2 cell_px, int n) {
+    vec2 lo = clamp(cell * cell_px - grid_origin, vec2(0.0), resolution);
+    vec2 hi = clamp((cell + 1.0) * cell_px - grid_origin, vec2(0.0), resolution);
+
+    ivec2 count = clamp(ivec2(ceil(hi - lo)), ivec2(1), ivec2(n));
+    vec3 sum = vec3(0.0);
+
+    for (int y = 0; y < count.y; y++) {
+        for (int x = 0; x < count.x; x++) {
+            vec2 pixel = lo + (vec2(x, y) + 0.5) / vec2(count) * (hi - lo);
+            sum += texture(img_texture, pixel / resolution).rgb;
+        }
+    }
+
+    return sum / float(count.x * count.y);
+}
+
 void main() {
-    vec2 pixel = TexPos * resolution;
-    vec2 cell = floor(pixel / cell_size);
+    // Cells are whole multiples of the size a source texel is drawn at (1 for the screen capture)
+    vec2 texel_px = max(vec2(1.0), floor(resolution / vec2(textureSize(img_texture, 0)) + 0.5));
+    vec2 cell_px = max(texel_px, floor(pixel_size * pixel_scale / texel_px + 0.5) * texel_px);
+
+    vec2 screen = grid_origin + TexPos * resolution;
+    vec2 cell = floor(screen / cell_px);
+
+    vec3 color = cell_color(cell, cell_px, MAX_SAMPLES);
+
+    if (outline > 0.0) {
+        // Outline on the darker side only, so lines stay one cell wide
+        float l = luminance(color);
+        float brightest = max(
+            max(luminance(cell_color(cell + vec2(1.0, 0.0), cell_px, 2)), luminance(cell_color(cell - vec2(1.0, 0.0), cell_px, 2))),
+            max(luminance(cell_color(cell + vec2(0.0, 1.0), cell_px, 2)), luminance(cell_color(cell - vec2(0.0, 1.0), cell_px, 2)))
+        );
+
+        if (brightest - l > outline_threshold) {
+            color = mix(color, color * 0.2, outline);
+        }
+    }
+
+    if (palette_size > 0) {
+        vec3 nearest = palette[0];
+
+        for (int i = 1; i < palette_size; i++) {
+            vec3 d = palette[i] - color;
+            vec3 dn = nearest - color;
+
+            if (dot(d, d) < dot(dn, dn)) {
+                nearest = palette[i];
+            }
+        }
 
-    // Read average color from a cell in the image
-    vec2 cell_uv = (cell * cell_size + cell_size * 0.5) / resolution;
-    vec3 avg_col = texture(img_texture, cell_uv).rgb;
+        color = nearest;
+    }
 
-    FragColor = vec4(avg_col, 1.0);
-}
\ No newline at end of file
+    FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,63 @@
+#version 330 core
+
+uniform sampler2D img_texture; // copy of the window, rows bottom-up
+uniform vec2 source_size;
+uniform vec2 output_size; // size of the Y plane
+
+out vec4 FragColor;
+
+// Average of the window pixels inside a rectangle given in top-down pixel coordinates
+vec3 area_average(vec2 top_left, vec2 size) {
+    ivec2 start = ivec2(floor(top_left));
+    ivec2 count = max(ivec2(1), ivec2(ceil(top_left + size)) - start);
+    ivec2 stride = max(ivec2(1), count / 16); // at most 16x16 samples per pixel
+
+    vec3 sum = vec3(0.0);
+    float n = 0.0;
+
+    for (int y = 0; y < count.y; y += stride.y) {
+        for (int x = 0; x < count.x; x += stride.x) {
+            ivec2 p = start + ivec2(x, y);
+            p.y = int(source_size.y) - 1 - p.y;
+
+            sum += texelFetch(img_texture, clamp(p, ivec2(0), ivec2(source_size) - 1), 0).rgb;
+            n += 1.0;
+        }
+    }
+
+    return sum / n;
+}
+
+void main() {
+    int width = int(output_size.x);
+    int height = int(output_size.y);
+    int packed_height = height + height / 2;
+
+    // Rows are flipped on readback, so packed row 0 is the first row in memory
+    int x = int(gl_FragCoord.x);
+    int row = packed_height - 1 - int(gl_FragCoord.y);
+    vec2 scale = source_size / output_size;
+
+    float value;
+
+    if (row < height) {
+        // Y plane
+        vec3 col = area_average(vec2(x, row) * scale, scale);
+        value = dot(col, vec3(0.299, 0.587, 0.114));
+    } else {
+        // Half size Cb plane followed by the Cr plane, one packed row holds two chroma rows
+        int chroma_width = width / 2;
+        int chroma_size = chroma_width * (height / 2);
+        int index = (row - height) * width + x;
+        bool is_cr = index >= chroma_size;
+        index -= is_cr ? chroma_size : 0;
+
+        vec2 p = vec2(index % chroma_width, index / chroma_width);
+        vec3 col = area_average(p * scale * 2.0, scale * 2.0);
+
+        value = is_cr ? 0.5 + dot(col, vec3(0.5, -0.418688, -0.081312))
+                      : 0.5 + dot(col, vec3(-0.168736, -0.331264, 0.5));
+    }
+
+    FragColor = vec4(value, 0.0, 0.0, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,86 @@
+#version 330 core
+
+uniform sampler2D img_texture; // effect output, same size as the window
+uniform int tone_map; // 0 only clips and encodes to sRGB, 1 Reinhard, 2 ACES, 3 AgX
+uniform bool linear_input; // false when the source is sRGB encoded
+
+out vec4 FragColor;
+
+vec3 srgb_to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 linear_to_srgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+// Reinhard on luminance, keeps the hue of bright colors
+vec3 reinhard(vec3 c) {
+    float l = dot(c, vec3(0.2126, 0.7152, 0.0722));
+
+    return c / (1.0 + l);
+}
+
+// Stephen Hill's fit of the ACES RRT and sRGB ODT
+vec3 aces(vec3 c) {
+    const mat3 input_matrix = mat3(
+        0.59719, 0.07600, 0.02840,
+        0.35458, 0.90834, 0.13383,
+        0.04823, 0.01566, 0.83777
+    );
+
+    const mat3 output_matrix = mat3(
+        1.60475, -0.10208, -0.00327,
+        -0.53108, 1.10813, -0.07276,
+        -0.07367, -0.00605, 1.07602
+    );
+
+    c = input_matrix * c;
+    c = (c * (c + 0.0245786) - 0.000090537) / (c * (0.983729 * c + 0.4329510) + 0.238081);
+
+    return output_matrix * c;
+}
+
+// Minimal AgX with the polynomial fit of the default contrast curve
+vec3 agx(vec3 c) {
+    const mat3 inset = mat3(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104
+    );
+
+    const mat3 outset = mat3(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116
+    );
+
+    const float min_ev = -12.47393;
+    const float max_ev = 4.026069;
+
+    c = inset * c;
+    c = clamp((log2(max(c, 1e-10)) - min_ev) / (max_ev - min_ev), 0.0, 1.0);
+
+    vec3 c2 = c * c;
+    vec3 c4 = c2 * c2;
+
+    c = 15.5 * c4 * c2 - 40.14 * c4 * c + 31.96 * c4 - 6.868 * c2 * c + 0.4298 * c2 + 0.1191 * c - 0.00232;
+
+    // Curve output is display encoded, back to linear for the shared sRGB encode below
+    return srgb_to_linear(clamp(outset * c, 0.0, 1.0));
+}
+
+void main() {
+    vec4 col = texelFetch(img_texture, ivec2(gl_FragCoord.xy), 0);
+    vec3 color = linear_input ? col.rgb : srgb_to_linear(max(col.rgb, 0.0));
+
+    if (tone_map == 1) {
+        color = reinhard(color);
+    } else if (tone_map == 2) {
+        color = aces(color);
+    } else if (tone_map == 3) {
+        color = agx(color);
+    }
+
+    FragColor = vec4(linear_to_srgb(clamp(color, 0.0, 1.0)), col.a);
+}
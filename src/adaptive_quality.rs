@@ -0,0 +1,108 @@
+use std::time::{ Duration, Instant };
+
+use crate::encoder::ShareFormat;
+use crate::share_hub::ShareStream;
+
+// (percent of the window size, max jpeg quality) from the best to the cheapest stream
+const LEVELS: [(usize, u8); 5] = [(50, 100), (50, 85), (40, 75), (30, 65), (20, 50)];
+const EVALUATE_INTERVAL: Duration = Duration::from_secs(2);
+const UPGRADE_AFTER: u32 = 3; // calm intervals in a row before the quality goes up again
+
+// Tracks throughput and queue depth of a single viewer and picks the stream it can keep up with
+pub struct AdaptiveQuality {
+    name: String,
+    requested: ShareFormat,
+    level: usize,
+    interval_start: Instant,
+    frames: u32,
+    bytes: usize,
+    send_time: Duration,
+    max_queue_depth: usize,
+    skipped: u64,
+    calm_intervals: u32
+}
+
+impl AdaptiveQuality {
+    pub fn new(name: &str, requested: ShareFormat) -> Self {
+        Self {
+            name: name.to_string(),
+            requested,
+            level: 0,
+            interval_start: Instant::now(),
+            frames: 0,
+            bytes: 0,
+            send_time: Duration::ZERO,
+            max_queue_depth: 0,
+            skipped: 0,
+            calm_intervals: 0
+        }
+    }
+
+    pub fn stream(&self) -> ShareStream {
+        let (scale_percent, max_quality) = LEVELS[self.level];
+
+        let format = match self.requested {
+            ShareFormat::Jpeg { quality, subsampling } => ShareFormat::Jpeg { quality: quality.min(max_quality), subsampling },
+            other => other
+        };
+
+        ShareStream { format, scale_percent }
+    }
+
+    // queue_depth = frames still waiting in the broadcast receiver after this one
+    pub fn record_frame(&mut self, bytes: usize, queue_depth: usize, send_time: Duration) {
+        self.frames += 1;
+        self.bytes += bytes;
+        self.send_time += send_time;
+        self.max_queue_depth = self.max_queue_depth.max(queue_depth);
+    }
+
+    pub fn record_lag(&mut self, skipped: u64) {
+        self.skipped += skipped;
+    }
+
+    // Returns true when the stream changed and the viewer has to resubscribe
+    pub fn evaluate(&mut self) -> bool {
+        let elapsed = self.interval_start.elapsed();
+
+        if elapsed < EVALUATE_INTERVAL {
+            return false;
+        }
+
+        // Fraction of the interval spent waiting for the socket
+        let busy = self.send_time.as_secs_f32() / elapsed.as_secs_f32();
+        let congested = self.skipped > 0 || self.max_queue_depth >= 2 || busy > 0.8;
+        let calm = self.skipped == 0 && self.max_queue_depth == 0 && busy < 0.4;
+
+        let previous_level = self.level;
+
+        if congested {
+            self.calm_intervals = 0;
+            self.level = (self.level + 1).min(LEVELS.len() - 1);
+        } else if calm {
+            self.calm_intervals += 1;
+
+            if self.calm_intervals >= UPGRADE_AFTER {
+                self.calm_intervals = 0;
+                self.level = self.level.saturating_sub(1);
+            }
+        } else {
+            self.calm_intervals = 0;
+        }
+
+        if self.level != previous_level {
+            let throughput = self.bytes as f32 / elapsed.as_secs_f32() / 1024.0;
+            let fps = self.frames as f32 / elapsed.as_secs_f32();
+            println!("{}: level {} -> {} ({:.0} KiB/s, {:.1} fps, {} skipped, queue {}) {:?}", self.name, previous_level, self.level, throughput, fps, self.skipped, self.max_queue_depth, self.stream());
+        }
+
+        self.interval_start = Instant::now();
+        self.frames = 0;
+        self.bytes = 0;
+        self.send_time = Duration::ZERO;
+        self.max_queue_depth = 0;
+        self.skipped = 0;
+
+        self.level != previous_level
+    }
+}
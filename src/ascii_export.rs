@@ -0,0 +1,296 @@
+use std::f32::consts::TAU;
+use std::fmt::Write;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use crate::effect_params::ParamSet;
+use crate::frame::Frame;
+
+// Glyph and color of one cell, as the ASCII shader picks them
+pub struct AsciiCell {
+    pub glyph: usize,
+    pub color: [u8; 3],
+    pub edge: bool
+}
+
+pub struct AsciiGrid {
+    pub columns: usize,
+    pub rows: usize,
+    pub cells: Vec<AsciiCell>
+}
+
+// Cell size and the "effect" parameters of the ASCII shader, modes are numbered like the shader's
+#[derive(Clone, Copy)]
+pub struct AsciiSettings {
+    pub cell_size: (f32, f32),
+    pub cell_average: i32, // 0 mean, 1 median, 2 dominant color
+    pub edge_detector: i32, // 0 Sobel, 1 Scharr, 2 difference of Gaussians
+    pub edge_threshold: f32,
+    pub edge_hysteresis: f32
+}
+
+impl AsciiSettings {
+    pub fn from_params(cell_size: (f32, f32), params: &ParamSet) -> AsciiSettings {
+        let get = |name: &str, default: f32| params.get(name).unwrap_or(default);
+
+        AsciiSettings {
+            cell_size,
+            cell_average: get("cell_average", 0.0).round() as i32,
+            edge_detector: get("edge_detector", 0.0).round() as i32,
+            edge_threshold: get("edge_threshold", 0.8),
+            edge_hysteresis: get("edge_hysteresis", 0.7)
+        }
+    }
+}
+
+const MAX_SAMPLES: usize = 8; // per axis, like the cell shader
+
+fn luminance(c: [f32; 3]) -> f32 {
+    c[0] * 0.299 + c[1] * 0.587 + c[2] * 0.114
+}
+
+// Same cells, edges and glyph selection as the ASCII shaders, for a window of the given size.
+// Edges of the previous grid are kept with hysteresis when it has the same size
+pub fn convert(source: &Frame, resolution: (i32, i32), settings: &AsciiSettings, glyph_count: i32, previous: Option<&AsciiGrid>) -> AsciiGrid {
+    let (width, height) = (resolution.0 as f32, resolution.1 as f32);
+    let cell_size = settings.cell_size;
+    let columns = (width / cell_size.0).ceil().max(1.0) as usize;
+    let rows = (height / cell_size.1).ceil().max(1.0) as usize;
+
+    let colors: Vec<[f32; 3]> = (0..columns * rows).map(|i| {
+        let (x, y) = ((i % columns) as f32, (i / columns) as f32);
+        let n = (cell_size.0.ceil().clamp(1.0, MAX_SAMPLES as f32) as usize, cell_size.1.ceil().clamp(1.0, MAX_SAMPLES as f32) as usize);
+
+        let samples: Vec<[f32; 3]> = (0..n.0 * n.1).map(|s| {
+            let u = (x * cell_size.0 + ((s % n.0) as f32 + 0.5) / n.0 as f32 * cell_size.0) / width;
+            let v = (y * cell_size.1 + ((s / n.0) as f32 + 0.5) / n.1 as f32 * cell_size.1) / height;
+
+            sample_bilinear(source, u, v)
+        }).collect();
+
+        average(&samples, settings.cell_average)
+    }).collect();
+
+    let lum = |x: i32, y: i32| luminance(colors[y.clamp(0, rows as i32 - 1) as usize * columns + x.clamp(0, columns as i32 - 1) as usize]);
+    let previous = previous.filter(|grid| grid.columns == columns && grid.rows == rows);
+
+    let mut cells = Vec::with_capacity(columns * rows);
+
+    for y in 0..rows as i32 {
+        for x in 0..columns as i32 {
+            let l = |dx: i32, dy: i32| lum(x + dx, y + dy);
+
+            let (tl, t, tr) = (l(-1, 1), l(0, 1), l(1, 1));
+            let (left, right) = (l(-1, 0), l(1, 0));
+            let (bl, b, br) = (l(-1, -1), l(0, -1), l(1, -1));
+
+            // Corner and side weights, Scharr is scaled to the magnitude of Sobel
+            let (wc, ws) = if settings.edge_detector == 1 { (0.75, 2.5) } else { (1.0, 2.0) };
+
+            let sx = -wc * tl - ws * left - wc * bl + wc * tr + ws * right + wc * br;
+            let sy = -wc * tl - ws * t - wc * tr + wc * bl + ws * b + wc * br;
+            let grad = (sx * sx + sy * sy).sqrt();
+
+            let mag = if settings.edge_detector == 2 {
+                let mut dog = 0.0;
+
+                for dy in -2..=2 {
+                    for dx in -2..=2 {
+                        let d2 = (dx * dx + dy * dy) as f32;
+                        dog += ((-d2 / 2.0).exp() / TAU - (-d2 / 5.12).exp() / (TAU * 2.56)) * l(dx, dy);
+                    }
+                }
+
+                dog.abs() * 20.0
+            } else {
+                grad
+            };
+
+            let index = y as usize * columns + x as usize;
+            let was_edge = previous.is_some_and(|grid| grid.cells[index].edge);
+            let edge = mag >= settings.edge_threshold || (was_edge && mag >= settings.edge_threshold * settings.edge_hysteresis);
+
+            let color = colors[index];
+            let direction = if grad > 0.0 { (sx / grad, sy / grad) } else { (0.0, 0.0) };
+
+            cells.push(AsciiCell { glyph: glyph_index(luminance(color), edge, direction, glyph_count), color: to_u8(color), edge });
+        }
+    }
+
+    AsciiGrid { columns, rows, cells }
+}
+
+// Grid of the cell and edge textures of the ASCII pre-pass (RGBA, rows in the order of the source), as the shader draws it
+pub fn grid_from_cells(columns: usize, rows: usize, cells: &[f32], edges: &[f32], glyph_count: i32) -> AsciiGrid {
+    let cells = cells.chunks_exact(4).zip(edges.chunks_exact(4)).map(|(cell, edge)| {
+        let is_edge = edge[0] > 0.5;
+
+        AsciiCell { glyph: glyph_index(cell[3], is_edge, (edge[1], edge[2]), glyph_count), color: to_u8([cell[0], cell[1], cell[2]]), edge: is_edge }
+    }).collect();
+
+    AsciiGrid { columns, rows, cells }
+}
+
+// Fill glyph by luminance, the last four glyphs are the edges - / \ | picked by the gradient direction
+fn glyph_index(luminance: f32, edge: bool, direction: (f32, f32), glyph_count: i32) -> usize {
+    let (gx, gy) = direction;
+
+    let glyph = if !edge {
+        ((luminance * (glyph_count - 5) as f32) as i32).clamp(0, glyph_count - 5)
+    } else if gx.abs() > gy.abs() * 1.5 {
+        glyph_count - 1
+    } else if gy.abs() > gx.abs() * 1.5 {
+        glyph_count - 4
+    } else if gx * gy > 0.0 {
+        glyph_count - 2
+    } else {
+        glyph_count - 3
+    };
+
+    glyph.max(0) as usize
+}
+
+fn to_u8(color: [f32; 3]) -> [u8; 3] {
+    color.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+// Mean, median by luminance or the mean of the most common color quantized to 8 levels per channel
+fn average(samples: &[[f32; 3]], mode: i32) -> [f32; 3] {
+    match mode {
+        1 => {
+            let mut sorted = samples.to_vec();
+            sorted.sort_by(|a, b| luminance(*a).total_cmp(&luminance(*b)));
+
+            sorted[sorted.len() / 2]
+        }
+
+        2 => {
+            let bin = |c: &[f32; 3]| c.map(|v| (v * 7.999) as i32);
+            let mut best: (usize, [f32; 3]) = (0, [0.0; 3]);
+
+            for sample in samples {
+                let matching: Vec<&[f32; 3]> = samples.iter().filter(|other| bin(other) == bin(sample)).collect();
+
+                if matching.len() > best.0 {
+                    best = (matching.len(), mean(&matching));
+                }
+            }
+
+            best.1
+        }
+
+        _ => mean(&samples.iter().collect::<Vec<_>>())
+    }
+}
+
+fn mean(samples: &[&[f32; 3]]) -> [f32; 3] {
+    let sum = samples.iter().fold([0.0; 3], |sum, c| [sum[0] + c[0], sum[1] + c[1], sum[2] + c[2]]);
+
+    sum.map(|v| v / samples.len() as f32)
+}
+
+// Linear filtering with clamped edges like the GL sampler, uv (0, 0) is the top left corner
+fn sample_bilinear(frame: &Frame, u: f32, v: f32) -> [f32; 3] {
+    let x = (u * frame.width as f32 - 0.5).clamp(0.0, (frame.width - 1) as f32);
+    let y = (v * frame.height as f32 - 0.5).clamp(0.0, (frame.height - 1) as f32);
+
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(frame.width - 1), (y0 + 1).min(frame.height - 1));
+    let (fx, fy) = (x.fract(), y.fract());
+
+    let pixel = |px: usize, py: usize, c: usize| frame.pixels[(py * frame.width + px) * frame.channels + c] as f32 / 255.0;
+
+    [0, 1, 2].map(|c| {
+        let c = c.min(frame.channels - 1);
+        let top = pixel(x0, y0, c) * (1.0 - fx) + pixel(x1, y0, c) * fx;
+        let bottom = pixel(x0, y1, c) * (1.0 - fx) + pixel(x1, y1, c) * fx;
+
+        top * (1.0 - fy) + bottom * fy
+    })
+}
+
+pub fn to_text(grid: &AsciiGrid, chars: &[char]) -> String {
+    let mut text = String::with_capacity((grid.columns + 1) * grid.rows);
+
+    for row in grid.cells.chunks(grid.columns) {
+        text.extend(row.iter().map(|cell| chars[cell.glyph]));
+        text.push('\n');
+    }
+
+    text
+}
+
+// 24-bit foreground colors, a new escape only where the color changes
+pub fn to_ansi(grid: &AsciiGrid, chars: &[char]) -> String {
+    let mut text = String::new();
+
+    for row in grid.cells.chunks(grid.columns) {
+        let mut last = None;
+
+        for cell in row {
+            if last != Some(cell.color) {
+                let [r, g, b] = cell.color;
+                let _ = write!(text, "\x1b[38;2;{};{};{}m", r, g, b);
+                last = Some(cell.color);
+            }
+
+            text.push(chars[cell.glyph]);
+        }
+
+        text.push_str("\x1b[0m\n");
+    }
+
+    text
+}
+
+// Colored runs in a pre block on black, like the effect draws them
+pub fn to_html(grid: &AsciiGrid, chars: &[char]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Shader_window ASCII</title>\n");
+    html.push_str("<style>body { background: #000; } pre { font-family: monospace; line-height: 1; }</style>\n</head>\n<body>\n<pre>");
+
+    for row in grid.cells.chunks(grid.columns) {
+        for run in row.chunk_by(|a, b| a.color == b.color) {
+            let [r, g, b] = run[0].color;
+            let _ = write!(html, "<span style=\"color:#{:02x}{:02x}{:02x}\">", r, g, b);
+
+            for cell in run {
+                match chars[cell.glyph] {
+                    '<' => html.push_str("&lt;"),
+                    '>' => html.push_str("&gt;"),
+                    '&' => html.push_str("&amp;"),
+                    c => html.push(c)
+                }
+            }
+
+            html.push_str("</span>");
+        }
+
+        html.push('\n');
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+
+    html
+}
+
+// Saves the grid as text, ANSI and HTML into the screenshots directory on a separate thread
+pub fn save_ascii_export(grid: AsciiGrid, chars: Vec<char>) {
+    std::thread::spawn(move || {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let base = format!("screenshots/ascii_{}", seconds);
+
+        let files = [
+            (format!("{}.txt", base), to_text(&grid, &chars)),
+            (format!("{}.ans", base), to_ansi(&grid, &chars)),
+            (format!("{}.html", base), to_html(&grid, &chars))
+        ];
+
+        let result = std::fs::create_dir_all("screenshots").map_err(|e| format!("Failed to create screenshots directory: {}", e)).and_then(|_| {
+            files.iter().try_for_each(|(path, content)| std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path, e)))
+        });
+
+        match result {
+            Ok(()) => println!("ASCII export saved: {}.txt, .ans, .html ({}x{} cells)", base, grid.columns, grid.rows),
+            Err(e) => println!("Failed to save ASCII export: {}", e)
+        }
+    });
+}
@@ -18,7 +18,7 @@ impl ScreenCapture {
         let screen_height = display.height();
 
         let capturer = Capturer::new(display).expect("Failed to create capturer");
-        let texture = Some(Texture::empty(screen_width as i32, screen_height as i32).expect("Failed to create texture"));
+        let texture = Some(Texture::new(screen_width as i32, screen_height as i32, gl::RGBA8).expect("Failed to create texture"));
 
         Self { capturer, screen_width, screen_height, texture }
     }
@@ -26,7 +26,12 @@ impl ScreenCapture {
     pub fn get_frame(&mut self, size: (usize, usize), pos: (usize, usize)) -> &Texture {
         let (width, height) = size;
 
-        // Recreate texture if size changed
+        // Minimized windows have no size, the last frame is kept until they are restored
+        if width == 0 || height == 0 {
+            return self.texture.as_ref().unwrap();
+        }
+
+        // Recreate texture if size changed, the old one is deleted when replaced
         let recreate = match &self.texture {
             Some(tex) => {
                 let (tw, th) = tex.get_texture_size();
@@ -37,7 +42,7 @@ impl ScreenCapture {
         };
 
         if recreate {
-            self.texture = Some(Texture::empty(width as i32, height as i32).expect("Failed to recreate texture"));
+            self.texture = Some(Texture::new(width as i32, height as i32, gl::RGBA8).expect("Failed to recreate texture"));
         }
 
         let texture = self.texture.as_ref().unwrap();
@@ -0,0 +1,125 @@
+use std::path::{ Path, PathBuf };
+
+#[derive(PartialEq, Debug)]
+pub enum LutKind {
+    OneD,
+    ThreeD
+}
+
+// Parsed .cube file. 3D data is ordered with red changing fastest, then green, then blue (the layout of a GL 3D texture)
+pub struct CubeLut {
+    pub title: String,
+    pub kind: LutKind,
+    pub size: usize,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    pub data: Vec<[f32; 3]>
+}
+
+impl CubeLut {
+    pub fn load(path: &Path) -> Result<CubeLut, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        CubeLut::parse(&content).map_err(|e| format!("Invalid LUT {}: {}", path.display(), e))
+    }
+
+    pub fn parse(content: &str) -> Result<CubeLut, String> {
+        let mut title = String::new();
+        let mut kind = None;
+        let mut size = 0;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut data = Vec::new();
+
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| format!("line {}: {}", number + 1, message);
+            let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+
+            match keyword {
+                "TITLE" => title = rest.trim_matches('"').to_string(),
+
+                "LUT_1D_SIZE" | "LUT_3D_SIZE" => {
+                    if kind.is_some() {
+                        return Err(error("more than one LUT size"));
+                    }
+
+                    size = rest.parse().map_err(|_| error("invalid LUT size"))?;
+
+                    let (new_kind, max_size) = if keyword == "LUT_1D_SIZE" { (LutKind::OneD, 65536) } else { (LutKind::ThreeD, 256) };
+
+                    if !(2..=max_size).contains(&size) {
+                        return Err(error(&format!("LUT size must be between 2 and {}", max_size)));
+                    }
+
+                    kind = Some(new_kind);
+                }
+
+                "DOMAIN_MIN" => domain_min = parse_triplet(rest).ok_or_else(|| error("invalid DOMAIN_MIN"))?,
+
+                "DOMAIN_MAX" => domain_max = parse_triplet(rest).ok_or_else(|| error("invalid DOMAIN_MAX"))?,
+
+                // Older Resolve files give the domain as one range for all channels
+                "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                    let range: Vec<f32> = rest.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+
+                    if range.len() != 2 {
+                        return Err(error("invalid input range"));
+                    }
+
+                    domain_min = [range[0]; 3];
+                    domain_max = [range[1]; 3];
+                }
+
+                _ if keyword.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') => {
+                    data.push(parse_triplet(line).ok_or_else(|| error("expected three numbers"))?);
+                }
+
+                // Unknown keywords of other tools are ignored
+                _ => {}
+            }
+        }
+
+        let kind = kind.ok_or("missing LUT_1D_SIZE or LUT_3D_SIZE")?;
+        let expected = if kind == LutKind::OneD { size } else { size * size * size };
+
+        if data.len() != expected {
+            return Err(format!("{} entries, expected {}", data.len(), expected));
+        }
+
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be greater than DOMAIN_MIN".to_string());
+        }
+
+        Ok(CubeLut { title, kind, size, domain_min, domain_max, data })
+    }
+}
+
+fn parse_triplet(text: &str) -> Option<[f32; 3]> {
+    let mut values = text.split_whitespace().map(|v| v.parse::<f32>());
+
+    let triplet = [values.next()?.ok()?, values.next()?.ok()?, values.next()?.ok()?];
+
+    values.next().is_none().then_some(triplet)
+}
+
+// .cube files of a directory sorted by name, none when it does not exist
+pub fn find_luts(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut luts: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("cube")))
+        .collect();
+
+    luts.sort();
+
+    luts
+}
@@ -0,0 +1,83 @@
+use crate::graphics::shader_reader::ShaderReader;
+
+// Float uniform that can be changed while running, the name is the name of the uniform
+pub struct Param {
+    pub name: &'static str,
+    pub value: f32,
+    min: f32,
+    max: f32,
+    step: f32,
+    group: &'static str
+}
+
+impl Param {
+    pub fn new(name: &'static str, value: f32, min: f32, max: f32, step: f32) -> Param {
+        Param { name, value, min, max, step, group: "" }
+    }
+}
+
+// Every runtime parameter, one of them is selected for adjusting. Groups belong to a shader (effect, grading, ...)
+pub struct ParamSet {
+    params: Vec<Param>,
+    selected: usize
+}
+
+impl ParamSet {
+    pub fn new() -> ParamSet {
+        ParamSet { params: Vec::new(), selected: 0 }
+    }
+
+    // Replace the parameters of a group, e.g. when the effect changes
+    pub fn set_group(&mut self, group: &'static str, params: Vec<Param>) {
+        self.params.retain(|param| param.group != group);
+        self.params.extend(params.into_iter().map(|param| Param { group, ..param }));
+        self.selected = self.selected.min(self.params.len().saturating_sub(1));
+    }
+
+    // Select the next (1) or previous (-1) parameter
+    pub fn select(&mut self, offset: i32) {
+        if self.params.is_empty() {
+            return;
+        }
+
+        let count = self.params.len() as i32;
+        self.selected = (self.selected as i32 + offset).rem_euclid(count) as usize;
+
+        let param = &self.params[self.selected];
+        println!("Selected parameter {} = {}", param.name, param.value);
+    }
+
+    // Change the selected parameter by a number of steps, within its range
+    pub fn adjust(&mut self, steps: f32) {
+        let Some(param) = self.params.get_mut(self.selected) else {
+            return;
+        };
+
+        // Rounded to whole steps so float drift doesn't add up
+        let value = ((param.value / param.step).round() + steps) * param.step;
+        param.value = value.clamp(param.min, param.max);
+
+        println!("{} = {}", param.name, param.value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.params.iter().find(|param| param.name == name).map(|param| param.value)
+    }
+
+    // Names and values of a group, in the order they were added
+    pub fn group_values(&self, group: &str) -> Vec<(&'static str, f32)> {
+        self.params.iter().filter(|param| param.group == group).map(|param| (param.name, param.value)).collect()
+    }
+
+    // Set the uniforms of a group on a bound shader, parameters the shader doesn't use are skipped
+    pub fn apply(&self, group: &str, shader: &mut ShaderReader) {
+        for param in self.params.iter().filter(|param| param.group == group) {
+            if !shader.has_uniform(param.name) {
+                continue;
+            }
+
+            shader.create_uniform(param.name);
+            shader.set_float_uniform(param.name, param.value);
+        }
+    }
+}
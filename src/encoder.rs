@@ -0,0 +1,247 @@
+use image::ExtendedColorType;
+use image::codecs::webp::WebPEncoder;
+use mozjpeg::{ Compress, ColorSpace };
+
+use crate::frame::{ Frame, Yuv420Frame };
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ChromaSubsampling {
+    Yuv444,
+    Yuv422,
+    Yuv420
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ShareFormat {
+    Jpeg { quality: u8, subsampling: ChromaSubsampling },
+    WebP { alpha: bool },
+    Png { alpha: bool },
+    RawZstd { alpha: bool }
+}
+
+pub trait FrameEncoder: Send {
+    fn encode(&mut self, frame: &Frame) -> Result<Vec<u8>, String>;
+
+    // Planes from the GPU conversion, encoders without a planar input convert them back to RGB
+    fn encode_yuv420(&mut self, frame: &Yuv420Frame) -> Result<Vec<u8>, String> {
+        self.encode(&frame.to_rgb())
+    }
+}
+
+impl Default for ShareFormat {
+    fn default() -> Self {
+        // Quality 60 is recommended but with downscale, text becomes completely unreadable and helped with maybe 2-5ms
+        // 4:2:0 chroma subsampling helped with maybe 2-5ms but worse quality
+        ShareFormat::Jpeg { quality: 100, subsampling: ChromaSubsampling::Yuv444 }
+    }
+}
+
+impl ShareFormat {
+    // Handshake message sent by a viewer, e.g. "jpeg;quality=80;subsampling=420", "webp", "png;alpha=1" or "rgba-zstd"
+    pub fn parse(message: &str) -> Result<ShareFormat, String> {
+        let mut parts = message.trim().split(';').map(str::trim);
+        let codec = parts.next().unwrap_or_default().to_lowercase();
+
+        let mut format = match codec.as_str() {
+            "jpeg" | "jpg" => ShareFormat::default(),
+            "webp" => ShareFormat::WebP { alpha: false },
+            "png" => ShareFormat::Png { alpha: false },
+            "rgba-zstd" | "raw" => ShareFormat::RawZstd { alpha: false },
+            _ => return Err(format!("Unknown share format: {}", codec))
+        };
+
+        for option in parts.filter(|p| !p.is_empty()) {
+            let (key, value) = option.split_once('=').ok_or_else(|| format!("Invalid format option: {}", option))?;
+
+            match (&mut format, key.trim()) {
+                (ShareFormat::Jpeg { quality, .. }, "quality") => {
+                    *quality = value.trim().parse::<u8>().map_err(|e| format!("Invalid jpeg quality: {}", e))?.clamp(1, 100);
+                },
+
+                (ShareFormat::Jpeg { subsampling, .. }, "subsampling") => {
+                    *subsampling = match value.trim() {
+                        "444" | "4:4:4" => ChromaSubsampling::Yuv444,
+                        "422" | "4:2:2" => ChromaSubsampling::Yuv422,
+                        "420" | "4:2:0" => ChromaSubsampling::Yuv420,
+                        other => return Err(format!("Invalid chroma subsampling: {}", other))
+                    };
+                },
+
+                (ShareFormat::WebP { alpha } | ShareFormat::Png { alpha } | ShareFormat::RawZstd { alpha }, "alpha") => {
+                    *alpha = match value.trim() {
+                        "1" | "true" => true,
+                        "0" | "false" => false,
+                        other => return Err(format!("Invalid alpha option: {}", other))
+                    };
+                },
+
+                (_, key) => return Err(format!("Option {} is not supported by {}", key, codec))
+            }
+        }
+
+        Ok(format)
+    }
+
+    // JPEG 4:2:0 can take the planes of the GPU conversion as they are, everything else is encoded from RGBA
+    pub fn takes_yuv420(&self) -> bool {
+        matches!(self, ShareFormat::Jpeg { subsampling: ChromaSubsampling::Yuv420, .. })
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ShareFormat::Jpeg { .. } => "image/jpeg",
+            ShareFormat::WebP { .. } => "image/webp",
+            ShareFormat::Png { .. } => "image/png",
+            ShareFormat::RawZstd { .. } => "application/zstd"
+        }
+    }
+
+    pub fn create_encoder(&self) -> Box<dyn FrameEncoder> {
+        match *self {
+            ShareFormat::Jpeg { quality, subsampling } => Box::new(JpegEncoder { quality, subsampling }),
+            ShareFormat::WebP { alpha } => Box::new(WebPLosslessEncoder { alpha }),
+            ShareFormat::Png { alpha } => Box::new(PngFrameEncoder { alpha }),
+            ShareFormat::RawZstd { alpha } => Box::new(RawZstdEncoder { level: 1, alpha, rgba: Vec::new() })
+        }
+    }
+}
+
+struct JpegEncoder {
+    quality: u8,
+    subsampling: ChromaSubsampling
+}
+
+impl FrameEncoder for JpegEncoder {
+    fn encode(&mut self, frame: &Frame) -> Result<Vec<u8>, String> {
+        // JFIF has no alpha and is read as sRGB, the alpha channel of RGBA input is ignored
+        let color_space = match frame.channels {
+            4 => ColorSpace::JCS_EXT_RGBA,
+            _ => ColorSpace::JCS_RGB
+        };
+
+        let mut comp = Compress::new(color_space);
+        comp.set_fastest_defaults();
+        comp.set_size(frame.width, frame.height);
+        comp.set_quality(self.quality as f32);
+
+        match self.subsampling {
+            ChromaSubsampling::Yuv444 => comp.set_chroma_sampling_pixel_sizes((1, 1), (1, 1)),
+            ChromaSubsampling::Yuv422 => comp.set_chroma_sampling_pixel_sizes((2, 1), (2, 1)),
+            ChromaSubsampling::Yuv420 => comp.set_chroma_sampling_pixel_sizes((2, 2), (2, 2))
+        }
+
+        let mut comp = comp.start_compress(Vec::new()).map_err(|e| format!("Failed to start jpeg compression: {}", e))?;
+        comp.write_scanlines(&frame.pixels).map_err(|e| format!("Failed to write jpeg scanlines: {}", e))?;
+
+        comp.finish().map_err(|e| format!("Failed to finish jpeg compression: {}", e))
+    }
+
+    fn encode_yuv420(&mut self, frame: &Yuv420Frame) -> Result<Vec<u8>, String> {
+        // Planes can only be passed through as they are when the stream asked for 4:2:0
+        if self.subsampling != ChromaSubsampling::Yuv420 {
+            return self.encode(&frame.to_rgb());
+        }
+
+        let mut comp = Compress::new(ColorSpace::JCS_YCbCr);
+        comp.set_fastest_defaults();
+        comp.set_size(frame.width, frame.height);
+        comp.set_quality(self.quality as f32);
+        comp.set_chroma_sampling_pixel_sizes((2, 2), (2, 2));
+        comp.set_raw_data_in(true);
+
+        let (y, cb, cr) = frame.planes();
+
+        let mut comp = comp.start_compress(Vec::new()).map_err(|e| format!("Failed to start jpeg compression: {}", e))?;
+
+        if !comp.write_raw_data(&[y, cb, cr]) {
+            return Err("Failed to write jpeg raw data".to_string());
+        }
+
+        comp.finish().map_err(|e| format!("Failed to finish jpeg compression: {}", e))
+    }
+}
+
+struct WebPLosslessEncoder {
+    alpha: bool
+}
+
+impl FrameEncoder for WebPLosslessEncoder {
+    fn encode(&mut self, frame: &Frame) -> Result<Vec<u8>, String> {
+        let (frame, color_type) = match self.alpha {
+            true => (frame.with_channels(4), ExtendedColorType::Rgba8),
+            false => (frame.with_channels(3), ExtendedColorType::Rgb8)
+        };
+
+        let mut data = Vec::new();
+
+        WebPEncoder::new_lossless(&mut data)
+            .encode(&frame.pixels, frame.width as u32, frame.height as u32, color_type)
+            .map_err(|e| format!("Failed to encode webp: {}", e))?;
+
+        Ok(data)
+    }
+}
+
+struct PngFrameEncoder {
+    alpha: bool
+}
+
+impl FrameEncoder for PngFrameEncoder {
+    fn encode(&mut self, frame: &Frame) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        encode_png(frame, self.alpha, &[], &mut data)?;
+
+        Ok(data)
+    }
+}
+
+// PNG tagged as sRGB, text = (keyword, text) pairs stored as tEXt chunks
+pub fn encode_png<W: std::io::Write>(frame: &Frame, alpha: bool, text: &[(&str, String)], writer: W) -> Result<(), String> {
+    let (frame, color_type) = match alpha {
+        true => (frame.with_channels(4), png::ColorType::Rgba),
+        false => (frame.with_channels(3), png::ColorType::Rgb)
+    };
+
+    let mut encoder = png::Encoder::new(writer, frame.width as u32, frame.height as u32);
+    encoder.set_color(color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Fast);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+
+    for (keyword, value) in text {
+        encoder.add_text_chunk(keyword.to_string(), value.clone()).map_err(|e| format!("Failed to add png text: {}", e))?;
+    }
+
+    let mut writer = encoder.write_header().map_err(|e| format!("Failed to write png header: {}", e))?;
+    writer.write_image_data(&frame.pixels).map_err(|e| format!("Failed to encode png: {}", e))?;
+
+    writer.finish().map_err(|e| format!("Failed to finish png: {}", e))
+}
+
+struct RawZstdEncoder {
+    level: i32,
+    alpha: bool,
+    rgba: Vec<u8>
+}
+
+impl FrameEncoder for RawZstdEncoder {
+    fn encode(&mut self, frame: &Frame) -> Result<Vec<u8>, String> {
+        // Expand to RGBA, the buffer is reused between frames
+        self.rgba.clear();
+        self.rgba.reserve(frame.width * frame.height * 4);
+
+        for px in frame.pixels.chunks_exact(frame.channels) {
+            let a = if self.alpha && frame.channels == 4 { px[3] } else { 255 };
+            self.rgba.extend_from_slice(&[px[0], px[1], px[2], a]);
+        }
+
+        // Header: width and height as little endian u32, followed by the zstd compressed RGBA pixels
+        let mut data = Vec::with_capacity(8 + self.rgba.len() / 4);
+        data.extend_from_slice(&(frame.width as u32).to_le_bytes());
+        data.extend_from_slice(&(frame.height as u32).to_le_bytes());
+
+        zstd::stream::copy_encode(self.rgba.as_slice(), &mut data, self.level).map_err(|e| format!("Failed to compress raw frame: {}", e))?;
+
+        Ok(data)
+    }
+}
@@ -0,0 +1,72 @@
+use std::borrow::Cow;
+
+// Top-down, tightly packed 8-bit sRGB pixels
+#[derive(Clone)]
+pub struct Frame {
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize // 1 = gray, 3 = RGB, 4 = RGBA
+}
+
+impl Frame {
+    pub fn new(pixels: Vec<u8>, width: usize, height: usize, channels: usize) -> Frame {
+        assert_eq!(pixels.len(), width * height * channels, "Frame size does not match its pixel data");
+
+        Frame { pixels, width, height, channels }
+    }
+
+    // Converts between RGB and RGBA, alpha of RGB input becomes opaque
+    pub fn with_channels(&self, channels: usize) -> Cow<'_, Frame> {
+        if channels == self.channels {
+            return Cow::Borrowed(self);
+        }
+
+        let pixels = match channels {
+            3 => self.pixels.chunks_exact(self.channels).flat_map(|px| [px[0], px[1], px[2]]).collect(),
+            _ => self.pixels.chunks_exact(self.channels).flat_map(|px| [px[0], px[1], px[2], 255]).collect()
+        };
+
+        Cow::Owned(Frame::new(pixels, self.width, self.height, channels))
+    }
+}
+
+// Planar 4:2:0 YCbCr, full range BT.601 like JFIF. Y plane, then Cb, then Cr, each half size chroma plane is contiguous
+pub struct Yuv420Frame {
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Yuv420Frame {
+    pub fn planes(&self) -> (&[u8], &[u8], &[u8]) {
+        let luma = self.width * self.height;
+        let chroma = (self.width / 2) * (self.height / 2);
+
+        let (y, rest) = self.pixels.split_at(luma);
+        let (cb, cr) = rest.split_at(chroma);
+
+        (y, cb, &cr[..chroma])
+    }
+
+    pub fn to_rgb(&self) -> Frame {
+        let (y_plane, cb_plane, cr_plane) = self.planes();
+        let chroma_width = self.width / 2;
+        let mut pixels = Vec::with_capacity(self.width * self.height * 3);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = (y / 2) * chroma_width + x / 2;
+                let luma = y_plane[y * self.width + x] as f32;
+                let cb = cb_plane[i] as f32 - 128.0;
+                let cr = cr_plane[i] as f32 - 128.0;
+
+                pixels.push((luma + 1.402 * cr).round().clamp(0.0, 255.0) as u8);
+                pixels.push((luma - 0.344136 * cb - 0.714136 * cr).round().clamp(0.0, 255.0) as u8);
+                pixels.push((luma + 1.772 * cb).round().clamp(0.0, 255.0) as u8);
+            }
+        }
+
+        Frame::new(pixels, self.width, self.height, 3)
+    }
+}
@@ -0,0 +1,188 @@
+use ab_glyph::{ Font, FontVec, PxScale, ScaleFont, point };
+use std::path::{ Path, PathBuf };
+
+use crate::frame::Frame;
+
+// Appended after the ramp in the order the ASCII shader expects: horizontal, /, \ and vertical edges
+const EDGE_GLYPHS: [char; 4] = ['-', '/', '\\', '|'];
+
+// Searched when SHADER_WINDOW_FONT is not set and the fonts directory has no font
+const SYSTEM_FONTS: [&str; 5] = [
+    "C:/Windows/Fonts/consola.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf"
+];
+
+pub struct GlyphRamp {
+    pub name: String,
+    pub chars: String
+}
+
+// White glyphs with coverage in alpha, the grid is read left to right, top to bottom
+pub struct GlyphAtlas {
+    pub frame: Frame,
+    pub grid: (i32, i32),
+    pub glyph_count: i32,
+    pub chars: Vec<char> // character of each glyph, for text output
+}
+
+impl GlyphAtlas {
+    // Hand-made atlas named like glyph_texture_(16x16x15).png: cell width, cell height and glyph count. Chars are the glyphs in atlas order
+    pub fn load_png(path: &Path, chars: &str) -> Result<GlyphAtlas, String> {
+        let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or_default();
+
+        let size: Vec<i32> = name.rsplit_once('(')
+            .and_then(|(_, size)| size.strip_suffix(')'))
+            .map(|size| size.split('x').filter_map(|v| v.parse().ok()).collect())
+            .unwrap_or_default();
+
+        let [cell_w, cell_h, glyph_count] = size[..] else {
+            return Err(format!("No (width x height x count) in the atlas name {}", path.display()));
+        };
+
+        let image = image::open(path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?.to_rgba8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let grid = (width as i32 / cell_w.max(1), height as i32 / cell_h.max(1));
+
+        if grid.0 * grid.1 < glyph_count {
+            return Err(format!("{} has room for {} glyphs, not {}", path.display(), grid.0 * grid.1, glyph_count));
+        }
+
+        let chars: Vec<char> = chars.chars().collect();
+
+        if chars.len() != glyph_count as usize {
+            return Err(format!("{} has {} glyphs, {} characters given", path.display(), glyph_count, chars.len()));
+        }
+
+        Ok(GlyphAtlas { frame: Frame::new(image.into_raw(), width, height, 4), grid, glyph_count, chars })
+    }
+
+    // Rasterize the ramp at the cell size, ordered from least to most covered, followed by the edge glyphs
+    pub fn build(font_path: &Path, ramp: &str, cell: (usize, usize)) -> Result<GlyphAtlas, String> {
+        let data = std::fs::read(font_path).map_err(|e| format!("Failed to read {}: {}", font_path.display(), e))?;
+        let font = FontVec::try_from_vec_and_index(data, 0).map_err(|e| format!("Invalid font {}: {}", font_path.display(), e))?;
+
+        let mut chars: Vec<char> = Vec::new();
+
+        for c in ramp.chars() {
+            if chars.contains(&c) {
+                continue;
+            }
+
+            if c != ' ' && font.glyph_id(c).0 == 0 {
+                println!("Font {} has no glyph for {:?}, skipped", font_path.display(), c);
+                continue;
+            }
+
+            chars.push(c);
+        }
+
+        if chars.len() < 2 {
+            return Err("A glyph ramp needs at least 2 characters".to_string());
+        }
+
+        // Whole line height fits the cell, scaled down further when the widest glyph doesn't
+        let mut scale = PxScale::from(cell.1 as f32);
+        let widest = chars.iter().chain(EDGE_GLYPHS.iter()).map(|&c| font.as_scaled(scale).h_advance(font.glyph_id(c))).fold(0.0, f32::max);
+
+        if widest > cell.0 as f32 {
+            scale = PxScale::from(cell.1 as f32 * cell.0 as f32 / widest);
+        }
+
+        let mut cells: Vec<(char, Vec<u8>, f32)> = chars.iter().chain(EDGE_GLYPHS.iter()).map(|&c| rasterize(&font, scale, c, cell)).collect();
+        cells[..chars.len()].sort_by(|a, b| a.2.total_cmp(&b.2));
+
+        // Close to square so long ramps stay within texture size limits
+        let count = cells.len();
+        let columns = (count as f32).sqrt().ceil() as usize;
+        let rows = count.div_ceil(columns);
+        let width = columns * cell.0;
+
+        let mut pixels = vec![0u8; width * rows * cell.1 * 4];
+
+        for (i, (_, coverage, _)) in cells.iter().enumerate() {
+            let (x0, y0) = (i % columns * cell.0, i / columns * cell.1);
+
+            for y in 0..cell.1 {
+                for x in 0..cell.0 {
+                    let p = ((y0 + y) * width + x0 + x) * 4;
+                    pixels[p..p + 4].copy_from_slice(&[255, 255, 255, coverage[y * cell.0 + x]]);
+                }
+            }
+        }
+
+        let chars = cells.iter().map(|(c, _, _)| *c).collect();
+
+        Ok(GlyphAtlas { frame: Frame::new(pixels, width, rows * cell.1, 4), grid: (columns as i32, rows as i32), glyph_count: count as i32, chars })
+    }
+}
+
+// Coverage of each pixel of the cell and the mean coverage, the glyph is centered on the advance with the baseline at the ascent
+fn rasterize(font: &FontVec, scale: PxScale, c: char, cell: (usize, usize)) -> (char, Vec<u8>, f32) {
+    let scaled = font.as_scaled(scale);
+    let id = font.glyph_id(c);
+
+    let x = (cell.0 as f32 - scaled.h_advance(id)) / 2.0;
+    let y = (cell.1 as f32 - scaled.height()) / 2.0 + scaled.ascent();
+
+    let mut coverage = vec![0u8; cell.0 * cell.1];
+
+    if let Some(outline) = font.outline_glyph(id.with_scale_and_position(scale, point(x, y))) {
+        let bounds = outline.px_bounds();
+
+        outline.draw(|gx, gy, c| {
+            let px = bounds.min.x as i32 + gx as i32;
+            let py = bounds.min.y as i32 + gy as i32;
+
+            if px >= 0 && py >= 0 && (px as usize) < cell.0 && (py as usize) < cell.1 {
+                let p = py as usize * cell.0 + px as usize;
+                coverage[p] = coverage[p].max((c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        });
+    }
+
+    let mean = coverage.iter().map(|&v| v as f32).sum::<f32>() / (255.0 * coverage.len() as f32);
+
+    (c, coverage, mean)
+}
+
+// Ramps of glyph_textures.txt: a "- Style N (Name)" line followed by the characters. \" and \\ are escapes
+pub fn read_ramps(path: &Path) -> Result<Vec<GlyphRamp>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut ramps = Vec::new();
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        let Some(title) = line.strip_prefix("- ") else {
+            continue;
+        };
+
+        let name = title.rsplit_once('(').and_then(|(_, name)| name.strip_suffix(')')).unwrap_or(title);
+
+        // Leading spaces are part of the ramp (the emptiest glyph)
+        if let Some(chars) = lines.by_ref().find(|l| !l.trim().is_empty()) {
+            let chars = chars.trim_end().replace("\\\"", "\"").replace("\\\\", "\\");
+            ramps.push(GlyphRamp { name: name.to_string(), chars });
+        }
+    }
+
+    Ok(ramps)
+}
+
+// SHADER_WINDOW_FONT, else the first font in the fonts directory, else a common monospace system font
+pub fn find_font() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("SHADER_WINDOW_FONT") {
+        return Some(PathBuf::from(path));
+    }
+
+    let mut fonts: Vec<PathBuf> = std::fs::read_dir("fonts").into_iter().flatten()
+        .filter_map(|entry| entry.ok()).map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()).is_some_and(|e| ["ttf", "otf", "ttc"].contains(&e.to_lowercase().as_str())))
+        .collect();
+
+    fonts.sort();
+
+    fonts.into_iter().next().or_else(|| SYSTEM_FONTS.iter().map(PathBuf::from).find(|path| path.is_file()))
+}
@@ -0,0 +1,108 @@
+use crate::ascii_export::{ AsciiGrid, grid_from_cells };
+use crate::effect_params::ParamSet;
+use crate::graphics::framebuffer::Framebuffer;
+use crate::graphics::quad::Quad;
+use crate::graphics::shader_reader::ShaderReader;
+use crate::graphics::texture::Texture;
+
+// Cell resolution passes of the ASCII effect: the color of each cell, then edges with hysteresis against the last frame
+pub struct AsciiPrepass {
+    cell_shader: ShaderReader,
+    edge_shader: ShaderReader,
+    cells: Option<Framebuffer>,
+    edges: [Option<Framebuffer>; 2], // this and the last frame, swapped every run
+}
+
+impl AsciiPrepass {
+    pub fn new() -> AsciiPrepass {
+        let mut cell_shader = ShaderReader::new("shaders/ascii/vertex_shader.glsl", "shaders/ascii/fragment_shader_cells.glsl");
+        cell_shader.bind();
+
+        cell_shader.create_uniform("img_texture");
+        cell_shader.set_int_uniform("img_texture", 0);
+
+        cell_shader.create_uniform("resolution");
+        cell_shader.create_uniform("cell_size");
+
+        let mut edge_shader = ShaderReader::new("shaders/ascii/vertex_shader.glsl", "shaders/ascii/fragment_shader_edges.glsl");
+        edge_shader.bind();
+
+        edge_shader.create_uniform("cell_texture");
+        edge_shader.set_int_uniform("cell_texture", 0);
+
+        edge_shader.create_uniform("previous_edges");
+        edge_shader.set_int_uniform("previous_edges", 1);
+
+        AsciiPrepass { cell_shader, edge_shader, cells: None, edges: [None, None] }
+    }
+
+    // Resolution is the size the effect is drawn at, the viewport is set back to the framebuffer size
+    pub fn run(&mut self, quad: &Quad, source: &Texture, resolution: (i32, i32), cell_size: (f32, f32), framebuffer_size: (i32, i32), params: &ParamSet) {
+        let columns = (resolution.0 as f32 / cell_size.0).ceil().max(1.0) as i32;
+        let rows = (resolution.1 as f32 / cell_size.1).ceil().max(1.0) as i32;
+
+        let mut previous = 0;
+
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous);
+        }
+
+        if self.cells.as_ref().is_none_or(|fb| fb.get_size() != (columns, rows)) {
+            self.cells = Some(Framebuffer::new(columns, rows, gl::RGBA16F).expect("Failed to create ASCII cell framebuffer"));
+
+            // New grid, the last frame has no edges
+            for edges in self.edges.iter_mut() {
+                let fb = Framebuffer::new(columns, rows, gl::RGBA16F).expect("Failed to create ASCII edge framebuffer");
+                fb.bind();
+
+                unsafe {
+                    gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+                    gl::Clear(gl::COLOR_BUFFER_BIT);
+                }
+
+                *edges = Some(fb);
+            }
+        }
+
+        let cells = self.cells.as_ref().unwrap();
+        self.edges.swap(0, 1);
+        let [edges, last_edges] = &self.edges;
+        let (edges, last_edges) = (edges.as_ref().unwrap(), last_edges.as_ref().unwrap());
+
+        cells.bind();
+        self.cell_shader.bind();
+        self.cell_shader.set_vec2_f32_uniform("resolution", resolution.0 as f32, resolution.1 as f32);
+        self.cell_shader.set_vec2_f32_uniform("cell_size", cell_size.0, cell_size.1);
+        params.apply("effect", &mut self.cell_shader);
+        source.bind(gl::TEXTURE0);
+        quad.draw();
+
+        edges.bind();
+        self.edge_shader.bind();
+        params.apply("effect", &mut self.edge_shader);
+        cells.get_texture().bind(gl::TEXTURE0);
+        last_edges.get_texture().bind(gl::TEXTURE1);
+        quad.draw();
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+            gl::Viewport(0, 0, framebuffer_size.0, framebuffer_size.1);
+        }
+    }
+
+    // Glyphs and colors of the last run read back from the cells and edges, exactly what the ASCII shader draws
+    pub fn download_grid(&self, glyph_count: i32) -> Option<AsciiGrid> {
+        let (cells, edges) = (self.cells.as_ref()?, self.edges[0].as_ref()?);
+        let (columns, rows) = cells.get_size();
+
+        Some(grid_from_cells(columns as usize, rows as usize, &cells.get_texture().download::<f32>(), &edges.get_texture().download::<f32>(), glyph_count))
+    }
+
+    // Cells and edges of the last run for the ASCII shader
+    pub fn bind_textures(&self, cells_unit: u32, edges_unit: u32) {
+        if let (Some(cells), Some(edges)) = (&self.cells, &self.edges[0]) {
+            cells.get_texture().bind(cells_unit);
+            edges.get_texture().bind(edges_unit);
+        }
+    }
+}
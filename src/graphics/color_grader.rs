@@ -0,0 +1,97 @@
+use crate::cube_lut::{ CubeLut, LutKind };
+use crate::effect_params::ParamSet;
+use crate::graphics::post_pass::PostPass;
+use crate::graphics::quad::Quad;
+use crate::graphics::shader_reader::ShaderReader;
+use crate::graphics::texture::Texture;
+use crate::graphics::texture_3d::Texture3d;
+
+enum LutTexture {
+    OneD(Texture),
+    ThreeD(Texture3d)
+}
+
+// Applies a .cube LUT to what was drawn to the window, with the lut_* parameters
+pub struct ColorGrader {
+    shader: ShaderReader,
+    pass: PostPass,
+    lut: Option<LutTexture>
+}
+
+impl ColorGrader {
+    pub fn new() -> ColorGrader {
+        let mut shader = ShaderReader::new("shaders/lut/vertex_shader.glsl", "shaders/lut/fragment_shader.glsl");
+        shader.bind();
+
+        shader.create_uniform("img_texture");
+        shader.set_int_uniform("img_texture", 0);
+
+        shader.create_uniform("lut_3d");
+        shader.set_int_uniform("lut_3d", 1);
+
+        shader.create_uniform("lut_1d");
+        shader.set_int_uniform("lut_1d", 2);
+
+        shader.create_uniform("is_3d");
+        shader.create_uniform("lut_size");
+        shader.create_uniform("domain_min");
+        shader.create_uniform("domain_max");
+
+        ColorGrader { shader, pass: PostPass::new(gl::RGBA8), lut: None }
+    }
+
+    // None turns grading off
+    pub fn set_lut(&mut self, lut: Option<&CubeLut>) -> Result<(), String> {
+        self.lut = None;
+
+        let Some(lut) = lut else {
+            return Ok(());
+        };
+
+        let size = lut.size as i32;
+
+        let texture = match lut.kind {
+            LutKind::OneD => {
+                let texture = Texture::new(size, 1, gl::RGBA32F)?;
+                let rgba: Vec<f32> = lut.data.iter().flat_map(|&[r, g, b]| [r, g, b, 1.0]).collect();
+                texture.upload(&rgba)?;
+
+                LutTexture::OneD(texture)
+            }
+
+            LutKind::ThreeD => LutTexture::ThreeD(Texture3d::new(size, &lut.data)?)
+        };
+
+        self.shader.bind();
+        self.shader.set_int_uniform("is_3d", (lut.kind == LutKind::ThreeD) as i32);
+        self.shader.set_float_uniform("lut_size", size as f32);
+        self.shader.set_vec3_f32_uniform("domain_min", lut.domain_min);
+        self.shader.set_vec3_f32_uniform("domain_max", lut.domain_max);
+
+        self.lut = Some(texture);
+
+        Ok(())
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.lut.is_some()
+    }
+
+    // Draw calls after this go to the grading target instead of the bound framebuffer
+    pub fn begin(&mut self, window_size: (i32, i32)) {
+        self.pass.begin(window_size);
+    }
+
+    // Grade what was drawn since begin into the framebuffer that was bound before
+    pub fn finish(&mut self, quad: &Quad, window_size: (i32, i32), params: &ParamSet) {
+        self.pass.finish(quad, window_size, &mut self.shader, |shader| {
+            params.apply("lut", shader);
+
+            match &self.lut {
+                Some(LutTexture::OneD(texture)) => texture.bind(gl::TEXTURE2),
+                Some(LutTexture::ThreeD(texture)) => texture.bind(gl::TEXTURE1),
+                None => {}
+            }
+        });
+    }
+}
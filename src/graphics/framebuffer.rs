@@ -0,0 +1,71 @@
+use gl::types::*;
+
+use crate::texture::Texture;
+
+// Offscreen render target backed by a texture, both are deleted on drop
+pub struct Framebuffer {
+    fbo: u32,
+    texture: Texture
+}
+
+impl Framebuffer {
+    pub fn new(width: i32, height: i32, internal_format: GLenum) -> Result<Framebuffer, String> {
+        let texture = Texture::new(width, height, internal_format)?;
+        let mut fbo = 0;
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, texture.get_id(), 0);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                gl::DeleteFramebuffers(1, &fbo);
+
+                return Err(format!("Framebuffer is incomplete: 0x{:x}", status));
+            }
+        }
+
+        Ok(Framebuffer { fbo, texture })
+    }
+
+    // Draw calls after this render into the texture
+    pub fn bind(&self) {
+        let (width, height) = self.texture.get_texture_size();
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, width, height);
+        }
+    }
+
+    // Back to the window, which has the given framebuffer size
+    pub fn unbind(&self, window_size: (i32, i32)) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(0, 0, window_size.0, window_size.1);
+        }
+    }
+
+    pub fn get_id(&self) -> u32 {
+        self.fbo
+    }
+
+    pub fn get_texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn get_size(&self) -> (i32, i32) {
+        self.texture.get_texture_size()
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+        }
+    }
+}
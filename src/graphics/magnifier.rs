@@ -0,0 +1,42 @@
+use crate::effect_params::ParamSet;
+use crate::graphics::post_pass::PostPass;
+use crate::graphics::quad::Quad;
+use crate::graphics::shader_reader::ShaderReader;
+
+// Zoom lens over what was drawn to the window, with the lens_* parameters
+pub struct Magnifier {
+    shader: ShaderReader,
+    pass: PostPass
+}
+
+impl Magnifier {
+    pub fn new() -> Magnifier {
+        let mut shader = ShaderReader::new("shaders/magnifier/vertex_shader.glsl", "shaders/magnifier/fragment_shader.glsl");
+        shader.bind();
+
+        shader.create_uniform("img_texture");
+        shader.set_int_uniform("img_texture", 0);
+
+        shader.create_uniform("lens_center");
+        shader.create_uniform("pixel_scale");
+
+        Magnifier { shader, pass: PostPass::new(gl::RGBA8) }
+    }
+
+    // Draw calls after this go to the lens target instead of the bound framebuffer
+    pub fn begin(&mut self, window_size: (i32, i32)) {
+        self.pass.begin(window_size);
+    }
+
+    // Draw what was drawn since begin with the lens at a window position (top-left origin) into the framebuffer bound before
+    pub fn finish(&mut self, quad: &Quad, window_size: (i32, i32), lens_pos: (f64, f64), pixel_scale: f32, params: &ParamSet) {
+        let x = lens_pos.0 as f32 * pixel_scale;
+        let y = window_size.1 as f32 - lens_pos.1 as f32 * pixel_scale;
+
+        self.pass.finish(quad, window_size, &mut self.shader, |shader| {
+            shader.set_vec2_f32_uniform("lens_center", x, y);
+            shader.set_float_uniform("pixel_scale", pixel_scale);
+            params.apply("lens", shader);
+        });
+    }
+}
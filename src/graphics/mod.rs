@@ -1,3 +1,14 @@
 pub mod window;
 pub mod texture;
 pub mod shader_reader;
+pub mod readback;
+pub mod framebuffer;
+pub mod quad;
+pub mod yuv_converter;
+pub mod tone_mapper;
+pub mod texture_3d;
+pub mod color_grader;
+pub mod ascii_prepass;
+pub mod structure_tensor;
+pub mod magnifier;
+pub mod post_pass;
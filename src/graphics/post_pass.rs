@@ -0,0 +1,57 @@
+use gl::types::*;
+
+use crate::graphics::framebuffer::Framebuffer;
+use crate::graphics::quad::Quad;
+use crate::graphics::shader_reader::ShaderReader;
+
+// Offscreen target for a full window pass: draw calls between begin and finish go into its texture,
+// which finish then draws with the pass's shader into the framebuffer that was bound before
+pub struct PostPass {
+    internal_format: GLenum,
+    target: Option<Framebuffer>,
+    previous: i32 // framebuffer bound before begin
+}
+
+impl PostPass {
+    pub fn new(internal_format: GLenum) -> PostPass {
+        PostPass { internal_format, target: None, previous: 0 }
+    }
+
+    // The target follows the window size, minimized windows have none and skip the pass
+    pub fn begin(&mut self, window_size: (i32, i32)) {
+        let (w, h) = window_size;
+
+        if w <= 0 || h <= 0 {
+            self.target = None;
+            return;
+        }
+
+        if self.target.as_ref().is_none_or(|fb| fb.get_size() != window_size) {
+            self.target = Some(Framebuffer::new(w, h, self.internal_format).expect("Failed to create post pass framebuffer"));
+        }
+
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut self.previous);
+        }
+
+        self.target.as_ref().unwrap().bind();
+    }
+
+    // Uniforms and extra textures of the shader are set by the closure, what was drawn since begin is on texture unit 0
+    pub fn finish(&self, quad: &Quad, window_size: (i32, i32), shader: &mut ShaderReader, set_uniforms: impl FnOnce(&mut ShaderReader)) {
+        let Some(target) = &self.target else {
+            return;
+        };
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous as u32);
+            gl::Viewport(0, 0, window_size.0, window_size.1);
+        }
+
+        shader.bind();
+        set_uniforms(shader);
+
+        target.get_texture().bind(gl::TEXTURE0);
+        quad.draw();
+    }
+}
@@ -0,0 +1,72 @@
+use std::ptr;
+
+// Two triangles covering the whole viewport
+pub struct Quad {
+    vao: u32,
+    vbo: u32,
+    ibo: u32
+}
+
+impl Quad {
+    pub fn new() -> Quad {
+        let mut vao = 0;
+        let mut vbo = 0;
+        let mut ibo = 0;
+
+        let vertices: Vec<f32> = vec![
+            // positions   // texture coords
+            1.0,  1.0,     1.0, 0.0,  // top right
+            1.0, -1.0,     1.0, 1.0,  // bottom right
+            -1.0, -1.0,    0.0, 1.0,  // bottom left
+            -1.0,  1.0,    0.0, 0.0   // top left
+        ];
+
+        let indices: Vec<u32> = vec![
+            0, 1, 3,  // first triangle
+            1, 2, 3   // second triangle
+        ];
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::GenBuffers(1, &mut ibo);
+
+            gl::BindVertexArray(vao);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * std::mem::size_of::<f32>()) as isize, vertices.as_ptr() as *const _, gl::STATIC_DRAW);
+
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ibo);
+            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * std::mem::size_of::<u32>()) as isize, indices.as_ptr() as *const _, gl::STATIC_DRAW);
+
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 4 * std::mem::size_of::<f32>() as i32, ptr::null());
+            gl::EnableVertexAttribArray(0);
+
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 4 * std::mem::size_of::<f32>() as i32, (2 * std::mem::size_of::<f32>()) as *const _);
+            gl::EnableVertexAttribArray(1);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+
+        Quad { vao, vbo, ibo }
+    }
+
+    pub fn draw(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for Quad {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteBuffers(1, &self.ibo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
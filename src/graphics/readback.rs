@@ -0,0 +1,181 @@
+use gl::types::*;
+use std::collections::VecDeque;
+use std::ptr;
+use std::sync::mpsc;
+use std::time::{ Duration, Instant };
+
+use crate::frame::Frame;
+
+struct PendingRead {
+    slot: usize,
+    fence: GLsync,
+    width: i32,
+    height: i32,
+    requested: Instant
+}
+
+// Ring of pixel buffer objects, frame N is copied out while N+1 renders instead of stalling on glReadPixels
+pub struct PixelReadback {
+    channels: usize, // 4 = RGBA, 1 = RED
+    buffers: Vec<u32>,
+    capacities: Vec<isize>,
+    pending: VecDeque<PendingRead>,
+    next_slot: usize,
+    recycle_tx: mpsc::Sender<Vec<u8>>,
+    recycle_rx: mpsc::Receiver<Vec<u8>>,
+    timings: ReadbackTimings
+}
+
+// Copy and latency of the reads since the last log line
+struct ReadbackTimings {
+    copy: Duration,
+    latency: Duration,
+    count: u32,
+    since: Instant
+}
+
+impl ReadbackTimings {
+    // Averages are printed once per second, not for every frame
+    fn record(&mut self, copy: Duration, latency: Duration) {
+        self.copy += copy;
+        self.latency += latency;
+        self.count += 1;
+
+        if self.since.elapsed() >= Duration::from_secs(1) {
+            println!("readback: {} reads, copy {:?}, latency {:?} on average", self.count, self.copy / self.count, self.latency / self.count);
+            *self = ReadbackTimings { copy: Duration::ZERO, latency: Duration::ZERO, count: 0, since: Instant::now() };
+        }
+    }
+}
+
+impl PixelReadback {
+    pub fn new(buffer_count: usize, channels: usize) -> PixelReadback {
+        let mut buffers = vec![0; buffer_count];
+
+        unsafe {
+            gl::GenBuffers(buffer_count as i32, buffers.as_mut_ptr());
+        }
+
+        let (recycle_tx, recycle_rx) = mpsc::channel();
+
+        PixelReadback { channels, buffers, capacities: vec![0; buffer_count], pending: VecDeque::new(), next_slot: 0, recycle_tx, recycle_rx, timings: ReadbackTimings { copy: Duration::ZERO, latency: Duration::ZERO, count: 0, since: Instant::now() } }
+    }
+
+    // Consumers send the pixel vectors of finished frames back, so they are reused instead of reallocated
+    pub fn recycler(&self) -> mpsc::Sender<Vec<u8>> {
+        self.recycle_tx.clone()
+    }
+
+    // Queue an asynchronous read of the bound read framebuffer (for the window before swap)
+    pub fn request(&mut self, width: i32, height: i32) {
+        // Ring is full, the consumer fell behind so the oldest read is dropped
+        if self.pending.len() == self.buffers.len() {
+            let dropped = self.pending.pop_front().unwrap();
+
+            unsafe {
+                gl::DeleteSync(dropped.fence);
+            }
+        }
+
+        let slot = self.next_slot;
+        self.next_slot = (self.next_slot + 1) % self.buffers.len();
+
+        let size = width as isize * height as isize * self.channels as isize;
+        let format = if self.channels == 4 { gl::RGBA } else { gl::RED };
+
+        unsafe {
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.buffers[slot]);
+
+            if self.capacities[slot] != size {
+                gl::BufferData(gl::PIXEL_PACK_BUFFER, size, ptr::null(), gl::STREAM_READ);
+                self.capacities[slot] = size;
+            }
+
+            // With a pack buffer bound the pointer is an offset into the buffer, so this returns immediately
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(0, 0, width, height, format, gl::UNSIGNED_BYTE, ptr::null_mut());
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+
+            let fence = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+            self.pending.push_back(PendingRead { slot, fence, width, height, requested: Instant::now() });
+        }
+    }
+
+    // Oldest read the GPU already finished, as a top-down frame with the time it was requested. Never waits for the GPU
+    pub fn poll(&mut self) -> Option<(Frame, Instant)> {
+        let read = self.pending.front()?;
+
+        let status = unsafe { gl::ClientWaitSync(read.fence, gl::SYNC_FLUSH_COMMANDS_BIT, 0) };
+
+        if status != gl::ALREADY_SIGNALED && status != gl::CONDITION_SATISFIED {
+            return None;
+        }
+
+        let read = self.pending.pop_front().unwrap();
+        let start = Instant::now();
+
+        let (w, h) = (read.width as usize, read.height as usize);
+        let row = w * self.channels;
+
+        let mut pixels = self.recycle_rx.try_recv().unwrap_or_default();
+        pixels.resize(row * h, 0);
+
+        unsafe {
+            gl::DeleteSync(read.fence);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.buffers[read.slot]);
+
+            let mapped = gl::MapBufferRange(gl::PIXEL_PACK_BUFFER, 0, (row * h) as isize, gl::MAP_READ_BIT) as *const u8;
+
+            if !mapped.is_null() {
+                let src = std::slice::from_raw_parts(mapped, row * h);
+
+                // Single copy out of the mapped buffer, rows are flipped on the way since OpenGL stores them bottom-up
+                for (y, dst) in pixels.chunks_exact_mut(row).enumerate() {
+                    let src_y = h - 1 - y;
+                    dst.copy_from_slice(&src[src_y * row..(src_y + 1) * row]);
+                }
+
+                gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+            }
+
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+
+            if mapped.is_null() {
+                println!("readback: failed to map pixel buffer");
+                return None;
+            }
+        }
+
+        self.timings.record(start.elapsed(), read.requested.elapsed());
+
+        Some((Frame::new(pixels, w, h, self.channels), read.requested))
+    }
+}
+
+impl Drop for PixelReadback {
+    fn drop(&mut self) {
+        unsafe {
+            for read in self.pending.drain(..) {
+                gl::DeleteSync(read.fence);
+            }
+
+            gl::DeleteBuffers(self.buffers.len() as i32, self.buffers.as_ptr());
+        }
+    }
+}
+
+// Blocking RGBA read of the bound read framebuffer as a top-down frame, for one-off captures like screenshots
+pub fn read_pixels(width: i32, height: i32) -> Frame {
+    let row = width as usize * 4;
+    let mut pixels = vec![0u8; row * height as usize];
+
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(0, 0, width, height, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
+    }
+
+    // OpenGL stores rows bottom-up
+    let flipped = pixels.chunks_exact(row).rev().flatten().copied().collect();
+
+    Frame::new(flipped, width as usize, height as usize, 4)
+}
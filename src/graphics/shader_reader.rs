@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{ BTreeMap, HashMap };
 use std::ffi::CString;
 use std::fs::File;
 use std::io::Read;
@@ -56,6 +56,13 @@ impl ShaderReader {
         }
     }
 
+    // Uniforms that are not used by the shader are removed when it is compiled
+    pub fn has_uniform(&self, uniform_name: &str) -> bool {
+        unsafe {
+            gl::GetUniformLocation(self.program_handle, CString::new(uniform_name).unwrap().as_ptr()) >= 0
+        }
+    }
+
     pub fn create_uniform(&mut self, uniform_name: &str) {
         let uniform_location = unsafe {
             gl::GetUniformLocation(self.program_handle, CString::new(uniform_name).unwrap().as_ptr())
@@ -91,4 +98,66 @@ impl ShaderReader {
             gl::Uniform2f(self.uniform_ids[uniform_name], x, y);
         }
     }
+
+    pub fn set_vec3_f32_uniform(&self, uniform_name: &str, value: [f32; 3]) {
+        unsafe {
+            gl::Uniform3f(self.uniform_ids[uniform_name], value[0], value[1], value[2]);
+        }
+    }
+
+    // Current values of the active uniforms read back from the program, sorted by name. Samplers are left out, vectors are joined with x
+    pub fn get_uniform_values(&self) -> BTreeMap<String, String> {
+        let mut values = BTreeMap::new();
+        let mut count = 0;
+
+        unsafe {
+            gl::GetProgramiv(self.program_handle, gl::ACTIVE_UNIFORMS, &mut count);
+        }
+
+        for index in 0..count.max(0) as u32 {
+            let mut name = [0u8; 256];
+            let (mut length, mut size, mut kind) = (0, 0, 0);
+
+            unsafe {
+                gl::GetActiveUniform(self.program_handle, index, name.len() as i32, &mut length, &mut size, &mut kind, name.as_mut_ptr() as *mut GLchar);
+            }
+
+            let (components, float) = match kind {
+                gl::FLOAT => (1, true),
+                gl::FLOAT_VEC2 => (2, true),
+                gl::FLOAT_VEC3 => (3, true),
+                gl::FLOAT_VEC4 => (4, true),
+                gl::INT | gl::BOOL => (1, false),
+                gl::INT_VEC2 => (2, false),
+                gl::INT_VEC3 => (3, false),
+                gl::INT_VEC4 => (4, false),
+                _ => continue
+            };
+
+            // Arrays are listed once as their first element, e.g. palette[0]
+            let name = String::from_utf8_lossy(&name[..length.max(0) as usize]).into_owned();
+            let name = name.strip_suffix("[0]").unwrap_or(&name);
+
+            for element in 0..size {
+                let element_name = if size > 1 { format!("{}[{}]", name, element) } else { name.to_string() };
+                let location = unsafe { gl::GetUniformLocation(self.program_handle, CString::new(element_name.as_str()).unwrap().as_ptr()) };
+
+                let value: Vec<String> = if float {
+                    let mut v = [0.0f32; 4];
+                    unsafe { gl::GetUniformfv(self.program_handle, location, v.as_mut_ptr()) };
+
+                    v[..components].iter().map(|c| c.to_string()).collect()
+                } else {
+                    let mut v = [0i32; 4];
+                    unsafe { gl::GetUniformiv(self.program_handle, location, v.as_mut_ptr()) };
+
+                    v[..components].iter().map(|c| c.to_string()).collect()
+                };
+
+                values.insert(element_name, value.join("x"));
+            }
+        }
+
+        values
+    }
 }
@@ -0,0 +1,85 @@
+use crate::graphics::framebuffer::Framebuffer;
+use crate::graphics::quad::Quad;
+use crate::graphics::shader_reader::ShaderReader;
+use crate::graphics::texture::Texture;
+
+// Smoothed structure tensor of the source at framebuffer size: tensor pass, then a horizontal and a vertical blur
+pub struct StructureTensor {
+    tensor_shader: ShaderReader,
+    blur_shader: ShaderReader,
+    targets: [Option<Framebuffer>; 2] // the result ends up in the first one
+}
+
+impl StructureTensor {
+    pub fn new() -> StructureTensor {
+        let mut tensor_shader = ShaderReader::new("shaders/kuwahara/vertex_shader.glsl", "shaders/kuwahara/fragment_shader_tensor.glsl");
+        tensor_shader.bind();
+
+        tensor_shader.create_uniform("img_texture");
+        tensor_shader.set_int_uniform("img_texture", 0);
+
+        tensor_shader.create_uniform("resolution");
+
+        let mut blur_shader = ShaderReader::new("shaders/kuwahara/vertex_shader.glsl", "shaders/kuwahara/fragment_shader_blur.glsl");
+        blur_shader.bind();
+
+        blur_shader.create_uniform("tensor_texture");
+        blur_shader.set_int_uniform("tensor_texture", 0);
+
+        blur_shader.create_uniform("direction");
+
+        StructureTensor { tensor_shader, blur_shader, targets: [None, None] }
+    }
+
+    // Resolution is the size the effect is drawn at, the targets have the framebuffer size which is also the viewport afterwards
+    pub fn run(&mut self, quad: &Quad, source: &Texture, resolution: (i32, i32), framebuffer_size: (i32, i32)) {
+        // Minimized window, nothing is drawn
+        if framebuffer_size.0 <= 0 || framebuffer_size.1 <= 0 {
+            return;
+        }
+
+        let mut previous = 0;
+
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous);
+        }
+
+        for target in self.targets.iter_mut() {
+            if target.as_ref().is_none_or(|fb| fb.get_size() != framebuffer_size) {
+                *target = Some(Framebuffer::new(framebuffer_size.0, framebuffer_size.1, gl::RGBA16F).expect("Failed to create structure tensor framebuffer"));
+            }
+        }
+
+        let [first, second] = &self.targets;
+        let (first, second) = (first.as_ref().unwrap(), second.as_ref().unwrap());
+
+        first.bind();
+        self.tensor_shader.bind();
+        self.tensor_shader.set_vec2_f32_uniform("resolution", resolution.0 as f32, resolution.1 as f32);
+        source.bind(gl::TEXTURE0);
+        quad.draw();
+
+        self.blur_shader.bind();
+
+        second.bind();
+        self.blur_shader.set_vec2_i32_uniform("direction", 1, 0);
+        first.get_texture().bind(gl::TEXTURE0);
+        quad.draw();
+
+        first.bind();
+        self.blur_shader.set_vec2_i32_uniform("direction", 0, 1);
+        second.get_texture().bind(gl::TEXTURE0);
+        quad.draw();
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+            gl::Viewport(0, 0, framebuffer_size.0, framebuffer_size.1);
+        }
+    }
+
+    pub fn bind_texture(&self, unit: u32) {
+        if let Some(target) = &self.targets[0] {
+            target.get_texture().bind(unit);
+        }
+    }
+}
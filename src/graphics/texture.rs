@@ -1,58 +1,164 @@
-use image::GenericImageView;
+use gl::types::*;
+use image::{ DynamicImage, GenericImageView };
 use std::ffi::c_void;
 use std::ptr::null;
 
-#[derive(Clone)]
+use crate::frame::Frame;
+
+// Component types that can be uploaded to and read back from a texture
+pub trait Texel: Copy + Default {
+    const GL_TYPE: GLenum;
+    const OPAQUE: Self; // alpha of 1
+}
+
+impl Texel for u8 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_BYTE;
+    const OPAQUE: u8 = 255;
+}
+
+impl Texel for f32 {
+    const GL_TYPE: GLenum = gl::FLOAT;
+    const OPAQUE: f32 = 1.0;
+}
+
+// Supported internal formats with the pixel format and the number of components they are uploaded as
+fn pixel_format(internal_format: GLenum) -> Result<(GLenum, usize), String> {
+    match internal_format {
+        gl::RGBA8 | gl::SRGB8_ALPHA8 | gl::RGBA16F | gl::RGBA32F => Ok((gl::RGBA, 4)),
+        gl::R8 => Ok((gl::RED, 1)),
+        _ => Err(format!("Unsupported texture format: 0x{:x}", internal_format))
+    }
+}
+
+// Owns the GL texture, it is deleted on drop
 pub struct Texture {
     id: u32,
     width: i32,
-    height: i32
+    height: i32,
+    internal_format: GLenum
 }
 
 impl Texture {
-    pub fn load_file(path: &str) -> Result<Texture, String> {
-        let img = image::open(path).map_err(|e| format!("Failed to load image: {}", e))?;
-        
+    // Linear filtering and clamped edges until changed
+    pub fn new(width: i32, height: i32, internal_format: GLenum) -> Result<Texture, String> {
+        let (format, _) = pixel_format(internal_format)?;
+
+        let mut max_size = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max_size) };
+
+        if width <= 0 || height <= 0 || width > max_size || height > max_size {
+            return Err(format!("Invalid texture size {}x{} (max {})", width, height, max_size));
+        }
+
         let mut id = 0;
-        let (width, height) = img.dimensions();
-        let data = img.to_rgba8().into_raw();
 
         unsafe {
             gl::GenTextures(1, &mut id);
+        }
+
+        // From here on the texture is deleted on drop, also when this fails
+        let texture = Texture { id, width, height, internal_format };
+        texture.set_filter(gl::LINEAR, gl::LINEAR);
+        texture.set_wrap(gl::CLAMP_TO_EDGE);
+
+        unsafe {
+            while gl::GetError() != gl::NO_ERROR {}
+
+            // allocate but do NOT upload data
             gl::BindTexture(gl::TEXTURE_2D, id);
-            
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-            
-            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, data.as_ptr() as *const _);
-            
+            gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as i32, width, height, 0, format, gl::UNSIGNED_BYTE, null());
             gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            let error = gl::GetError();
+
+            if error != gl::NO_ERROR {
+                return Err(format!("Failed to allocate {}x{} texture: 0x{:x}", width, height, error));
+            }
         }
-        
-        Ok(Texture { id, width: width as i32, height: height as i32 })
+
+        Ok(texture)
     }
 
-    pub fn empty(width: i32, height: i32) -> Result<Texture, String> {
-        let mut id = 0;
+    // 8-bit images become RGBA8 (gray ones R8 shown as gray), 16-bit and float images keep their precision in float textures
+    pub fn load_file(path: &str) -> Result<Texture, String> {
+        let img = image::open(path).map_err(|e| format!("Failed to load image: {}", e))?;
+        let (width, height) = img.dimensions();
+        let (width, height) = (width as i32, height as i32);
 
-        unsafe {
-            gl::GenTextures(1, &mut id);
-            gl::BindTexture(gl::TEXTURE_2D, id);
+        match img {
+            DynamicImage::ImageLuma8(gray) => {
+                let texture = Texture::new(width, height, gl::R8)?;
+                texture.upload(gray.as_raw())?;
 
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+                unsafe {
+                    let swizzle = [gl::RED as i32, gl::RED as i32, gl::RED as i32, gl::ONE as i32];
 
-            // allocate but do NOT upload data
-            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as i32, width, height, 0, gl::BGRA, gl::UNSIGNED_BYTE, null());
+                    gl::BindTexture(gl::TEXTURE_2D, texture.id);
+                    gl::TexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+                    gl::BindTexture(gl::TEXTURE_2D, 0);
+                }
+
+                Ok(texture)
+            }
+
+            DynamicImage::ImageLuma16(_) | DynamicImage::ImageLumaA16(_) | DynamicImage::ImageRgb16(_) | DynamicImage::ImageRgba16(_) => {
+                let texture = Texture::new(width, height, gl::RGBA16F)?;
+                texture.upload(img.to_rgba32f().as_raw())?;
+
+                Ok(texture)
+            }
+
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                let texture = Texture::new(width, height, gl::RGBA32F)?;
+                texture.upload(img.to_rgba32f().as_raw())?;
+
+                Ok(texture)
+            }
+
+            _ => {
+                let texture = Texture::new(width, height, gl::RGBA8)?;
+                texture.upload(img.to_rgba8().as_raw())?;
+
+                Ok(texture)
+            }
+        }
+    }
+
+    // Replace the whole texture, data has one value per component of the format (4 for RGBA, 1 for R8)
+    pub fn upload<T: Texel>(&self, data: &[T]) -> Result<(), String> {
+        let (format, components) = pixel_format(self.internal_format)?;
+        let expected = self.width as usize * self.height as usize * components;
+
+        if data.len() != expected {
+            return Err(format!("Texture upload has {} values, expected {}", data.len(), expected));
+        }
+
+        unsafe {
+            // Rows are tightly packed, the previous alignment is restored for other uploads
+            let mut alignment = 4;
+            gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
 
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(gl::TEXTURE_2D, 0, 0, 0, self.width, self.height, format, T::GL_TYPE, data.as_ptr() as *const _);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
-        Ok(Texture { id, width, height })
+        Ok(())
+    }
+
+    // Frame of the same size, converted to the channels of the texture
+    pub fn upload_frame(&self, frame: &Frame) -> Result<(), String> {
+        if (frame.width as i32, frame.height as i32) != (self.width, self.height) {
+            return Err(format!("Frame is {}x{}, texture is {}x{}", frame.width, frame.height, self.width, self.height));
+        }
+
+        match pixel_format(self.internal_format)? {
+            (_, 4) => self.upload(&frame.with_channels(4).pixels),
+            _ if frame.channels == 1 => self.upload(&frame.pixels),
+            _ => Err("Only gray frames can be uploaded to single channel textures".to_string())
+        }
     }
 
     pub fn update_from_ptr(&self, size: (usize, usize), pos: (usize, usize), stride: usize, frame_ptr: *const c_void) {
@@ -73,7 +179,65 @@ impl Texture {
             gl::PixelStorei(gl::UNPACK_SKIP_ROWS, 0);
         }
     }
-    
+
+    // Level 0 as RGBA, rows in upload order (top-down for loaded images and captures). R8 is expanded to gray like it is drawn
+    pub fn download<T: Texel>(&self) -> Vec<T> {
+        let (format, components) = pixel_format(self.internal_format).unwrap_or((gl::RGBA, 4));
+        let mut data = vec![T::default(); self.width as usize * self.height as usize * components];
+
+        unsafe {
+            let mut alignment = 4;
+            gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut alignment);
+
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTexImage(gl::TEXTURE_2D, 0, format, T::GL_TYPE, data.as_mut_ptr() as *mut _);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, alignment);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        if components == 1 {
+            return data.into_iter().flat_map(|v| [v, v, v, T::OPAQUE]).collect();
+        }
+
+        data
+    }
+
+    pub fn download_frame(&self) -> Frame {
+        Frame::new(self.download::<u8>(), self.width as usize, self.height as usize, 4)
+    }
+
+    // Smaller levels for minification, sampled with trilinear filtering
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        self.set_filter(gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR);
+    }
+
+    // gl::NEAREST, gl::LINEAR or for min also the gl::*_MIPMAP_* filters
+    pub fn set_filter(&self, min: GLenum, mag: GLenum) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    // gl::CLAMP_TO_EDGE, gl::REPEAT or gl::MIRRORED_REPEAT
+    pub fn set_wrap(&self, wrap: GLenum) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
     pub fn bind(&self, texture_id: u32) {
         unsafe {
             gl::ActiveTexture(texture_id);
@@ -90,4 +254,16 @@ impl Texture {
     pub fn get_texture_size(&self) -> (i32, i32) {
         (self.width, self.height)
     }
+
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
 }
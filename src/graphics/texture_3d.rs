@@ -0,0 +1,72 @@
+use std::ffi::c_void;
+
+// RGB float volume with linear filtering and clamped edges, used for color lookup tables. Deleted on drop
+pub struct Texture3d {
+    id: u32
+}
+
+impl Texture3d {
+    // size^3 RGB values, x changing fastest
+    pub fn new(size: i32, data: &[[f32; 3]]) -> Result<Texture3d, String> {
+        let mut max_size = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_3D_TEXTURE_SIZE, &mut max_size) };
+
+        if size <= 0 || size > max_size {
+            return Err(format!("Invalid 3D texture size {} (max {})", size, max_size));
+        }
+
+        if data.len() != (size * size * size) as usize {
+            return Err(format!("3D texture has {} values, expected {}", data.len(), size * size * size));
+        }
+
+        let mut id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut id);
+        }
+
+        let texture = Texture3d { id };
+
+        unsafe {
+            while gl::GetError() != gl::NO_ERROR {}
+
+            gl::BindTexture(gl::TEXTURE_3D, id);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+
+            let mut alignment = 4;
+            gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
+
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage3D(gl::TEXTURE_3D, 0, gl::RGB32F as i32, size, size, size, 0, gl::RGB, gl::FLOAT, data.as_ptr() as *const c_void);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+
+            let error = gl::GetError();
+
+            if error != gl::NO_ERROR {
+                return Err(format!("Failed to allocate {}^3 texture: 0x{:x}", size, error));
+            }
+        }
+
+        Ok(texture)
+    }
+
+    pub fn bind(&self, texture_id: u32) {
+        unsafe {
+            gl::ActiveTexture(texture_id);
+            gl::BindTexture(gl::TEXTURE_3D, self.id);
+        }
+    }
+}
+
+impl Drop for Texture3d {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::graphics::post_pass::PostPass;
+use crate::graphics::quad::Quad;
+use crate::graphics::shader_reader::ShaderReader;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToneMap {
+    Off,
+    Reinhard,
+    Aces,
+    Agx
+}
+
+impl ToneMap {
+    pub fn next(self) -> Self {
+        match self {
+            ToneMap::Off => ToneMap::Reinhard,
+            ToneMap::Reinhard => ToneMap::Aces,
+            ToneMap::Aces => ToneMap::Agx,
+            ToneMap::Agx => ToneMap::Off
+        }
+    }
+}
+
+// Effects draw into a float target in the scene's (possibly linear HDR) values, which is then tone mapped to the window
+pub struct ToneMapper {
+    shader: ShaderReader,
+    pass: PostPass
+}
+
+impl ToneMapper {
+    pub fn new() -> ToneMapper {
+        let mut shader = ShaderReader::new("shaders/tone_map/vertex_shader.glsl", "shaders/tone_map/fragment_shader.glsl");
+        shader.bind();
+
+        shader.create_uniform("img_texture");
+        shader.set_int_uniform("img_texture", 0);
+
+        shader.create_uniform("tone_map");
+        shader.create_uniform("linear_input");
+
+        ToneMapper { shader, pass: PostPass::new(gl::RGBA16F) }
+    }
+
+    // Draw calls after this go to the float target instead of the bound framebuffer
+    pub fn begin(&mut self, window_size: (i32, i32)) {
+        self.pass.begin(window_size);
+    }
+
+    // Tone map what was drawn since begin into the framebuffer bound before, linear_input is false for sRGB encoded sources
+    pub fn finish(&mut self, quad: &Quad, window_size: (i32, i32), tone_map: ToneMap, linear_input: bool) {
+        let operator = match tone_map {
+            ToneMap::Off => 0,
+            ToneMap::Reinhard => 1,
+            ToneMap::Aces => 2,
+            ToneMap::Agx => 3
+        };
+
+        self.pass.finish(quad, window_size, &mut self.shader, |shader| {
+            shader.set_int_uniform("tone_map", operator);
+            shader.set_int_uniform("linear_input", linear_input as i32);
+        });
+    }
+}
@@ -2,6 +2,7 @@ use glfw::{ Action, Context, Key, WindowEvent };
 use glfw::ffi::GLFWwindow;
 use glfw::WindowMode::{ Windowed, FullScreen};
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 pub struct Window {
     glfw: glfw::Glfw,
@@ -9,6 +10,8 @@ pub struct Window {
     events: glfw::GlfwReceiver<(f64, WindowEvent)>,
     keys_pressed: HashSet<Key>,
     keys_released: HashSet<Key>,
+    dropped_files: Vec<PathBuf>,
+    cursor_pos: (f64, f64),
     windowed_pos: (i32, i32),
     windowed_size: (i32, i32),
     fullscreen: bool,
@@ -23,10 +26,12 @@ impl Window {
 
         window.set_framebuffer_size_polling(true);
         window.set_key_polling(true);
+        window.set_drag_and_drop_polling(true);
+        window.set_cursor_pos_polling(true);
 
         let (w, h) = window.get_size();
 
-        Window { glfw, window_handle: window, events, keys_pressed: HashSet::new(), keys_released: HashSet::new(), windowed_pos: (0, 0), windowed_size: (w, h), fullscreen: false, overlay_mode: false }
+        Window { glfw, window_handle: window, events, keys_pressed: HashSet::new(), keys_released: HashSet::new(), dropped_files: Vec::new(), cursor_pos: (0.0, 0.0), windowed_pos: (0, 0), windowed_size: (w, h), fullscreen: false, overlay_mode: false }
     }
 
     pub fn init_gl(&mut self) {
@@ -76,11 +81,29 @@ impl Window {
                     _ => {}
                 },
 
+                glfw::WindowEvent::FileDrop(paths) => {
+                    self.dropped_files.extend(paths);
+                },
+
+                glfw::WindowEvent::CursorPos(x, y) => {
+                    self.cursor_pos = (x, y);
+                },
+
                 _ => {}
             }
         }
     }
 
+    // Last cursor position in window coordinates, top-left origin. The overlay lets the mouse through and gets no
+    // cursor events, so there the position is read from the system instead
+    pub fn get_cursor_pos(&self) -> (f64, f64) {
+        if self.overlay_mode {
+            return self.window_handle.get_cursor_pos();
+        }
+
+        self.cursor_pos
+    }
+
     pub fn is_key_pressed(&self, key: Key) -> bool {
         self.keys_pressed.contains(&key)
     }
@@ -89,6 +112,11 @@ impl Window {
         self.keys_released.contains(&key)
     }
 
+    // Files dropped onto the window since the last call
+    pub fn take_dropped_files(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.dropped_files)
+    }
+
     pub fn set_fps(&mut self, interval: i32) {
         self.window_handle.make_current();
         match interval {
@@ -118,10 +146,32 @@ impl Window {
         self.windowed_size = (size.0, size.1);
     }
 
+    pub fn get_framebuffer_size(&self) -> (i32, i32) {
+        self.window_handle.get_framebuffer_size()
+    }
+
     pub fn get_window_pos(&self) -> (i32, i32) {
         self.window_handle.get_pos()
     }
 
+    // Window pixels per inch of the primary monitor, 96 scaled by the content scale when the monitor doesn't report its size
+    pub fn get_dpi(&mut self) -> f32 {
+        let fallback = 96.0 * self.window_handle.get_content_scale().0;
+
+        self.glfw.with_primary_monitor(|_, m| {
+            let Some(monitor) = m else {
+                return fallback;
+            };
+
+            let (width_mm, _) = monitor.get_physical_size();
+
+            match monitor.get_video_mode() {
+                Some(mode) if width_mm > 0 => mode.width as f32 / (width_mm as f32 / 25.4),
+                _ => fallback
+            }
+        })
+    }
+
     pub fn set_fullscreen(&mut self, fullscreen: bool) {
         if fullscreen == self.fullscreen {
             return;
@@ -192,17 +242,4 @@ impl Window {
     pub fn get_window_ptr(&self) -> *mut GLFWwindow {
         self.window_handle.window_ptr()
     }
-
-    pub fn read_pixels(&self) -> Vec<u8> {
-        // Read framebuffer before swap
-        let (w, h) = self.window_handle.get_framebuffer_size();
-        let mut pixels = vec![0u8; (w * h * 3) as usize]; // RGB = 3 bytes per pixel
-
-        unsafe {
-            gl::PixelStorei(gl::PACK_ALIGNMENT, 1); // Rows tightly packed (needed for RGB, not for RGBA)
-            gl::ReadPixels(0, 0, w, h, gl::RGB, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
-        }
-
-        pixels
-    }
 }
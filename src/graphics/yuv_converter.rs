@@ -0,0 +1,94 @@
+use std::sync::mpsc;
+
+use crate::frame::Yuv420Frame;
+use crate::graphics::framebuffer::Framebuffer;
+use crate::graphics::quad::Quad;
+use crate::graphics::readback::PixelReadback;
+use crate::graphics::shader_reader::ShaderReader;
+
+// Downscales the window and converts it to planar YUV 4:2:0 on the GPU, so only the planes are read back
+pub struct YuvConverter {
+    shader: ShaderReader,
+    source: Option<Framebuffer>,
+    planes: Option<Framebuffer>,
+    readback: PixelReadback
+}
+
+impl YuvConverter {
+    pub fn new() -> YuvConverter {
+        let mut shader = ShaderReader::new("shaders/share_yuv/vertex_shader.glsl", "shaders/share_yuv/fragment_shader.glsl");
+        shader.bind();
+
+        shader.create_uniform("img_texture");
+        shader.set_int_uniform("img_texture", 0);
+
+        shader.create_uniform("source_size");
+        shader.create_uniform("output_size");
+
+        YuvConverter { shader, source: None, planes: None, readback: PixelReadback::new(3, 1) }
+    }
+
+    // Size of the Y plane for a share scale, multiples of 16 so the planes fill whole jpeg MCUs
+    pub fn output_size(window_size: (i32, i32), scale_percent: usize) -> (i32, i32) {
+        let w = window_size.0 * scale_percent as i32 / 100;
+        let h = window_size.1 * scale_percent as i32 / 100;
+
+        ((w / 16 * 16).max(16), (h / 16 * 16).max(16))
+    }
+
+    pub fn recycler(&self) -> mpsc::Sender<Vec<u8>> {
+        self.readback.recycler()
+    }
+
+    // Convert what was drawn to the window this frame (before swap) and queue the read of the planes
+    pub fn request(&mut self, quad: &Quad, window_size: (i32, i32), scale_percent: usize) {
+        let (w, h) = window_size;
+
+        // Minimized window, there is nothing to convert
+        if w <= 0 || h <= 0 {
+            return;
+        }
+
+        let (out_w, out_h) = YuvConverter::output_size(window_size, scale_percent);
+        let packed_size = (out_w, out_h + out_h / 2);
+
+        if self.source.as_ref().is_none_or(|fb| fb.get_size() != window_size) {
+            self.source = Some(Framebuffer::new(w, h, gl::RGBA8).expect("Failed to create share source framebuffer"));
+        }
+
+        if self.planes.as_ref().is_none_or(|fb| fb.get_size() != packed_size) {
+            self.planes = Some(Framebuffer::new(packed_size.0, packed_size.1, gl::R8).expect("Failed to create share planes framebuffer"));
+        }
+
+        let source = self.source.as_ref().unwrap();
+        let planes = self.planes.as_ref().unwrap();
+
+        unsafe {
+            // Window contents can not be sampled directly, copy them into a texture first
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, source.get_id());
+            gl::BlitFramebuffer(0, 0, w, h, 0, 0, w, h, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+        }
+
+        // All three planes are drawn in one pass into a single R8 target laid out as I420
+        planes.bind();
+        self.shader.bind();
+        self.shader.set_vec2_f32_uniform("source_size", w as f32, h as f32);
+        self.shader.set_vec2_f32_uniform("output_size", out_w as f32, out_h as f32);
+        source.get_texture().bind(gl::TEXTURE0);
+        quad.draw();
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, planes.get_id());
+        }
+
+        self.readback.request(packed_size.0, packed_size.1);
+        planes.unbind(window_size);
+    }
+
+    pub fn poll(&mut self) -> Option<Yuv420Frame> {
+        let (packed, _) = self.readback.poll()?;
+
+        Some(Yuv420Frame { width: packed.width, height: packed.height * 2 / 3, pixels: packed.pixels })
+    }
+}
@@ -1,33 +1,18 @@
+use std::path::Path;
+
 use crate::graphics::*;
+use crate::graphics::texture::Texture;
+use crate::effect_params::Param;
+use crate::glyph_atlas::{ GlyphAtlas, GlyphRamp, find_font };
+use crate::palette::MAX_PALETTE_COLORS;
 use crate::select_shader::SelectShader;
 
-pub fn load_test_image(index: &mut usize) -> texture::Texture{
-    let test_images = vec![
-        "assets/test_image_acerola_example.png",
-        "assets/test_image_astroneer_gameplay.png",
-        "assets/test_image_chrome_screenshot.png",
-        "assets/test_image_destiny_2_poster.png",
-        "assets/test_image_destiny_2_rasputin.png",
-        "assets/test_image_destiny_2_witness.png",
-        "assets/test_image_dont_starve_gameplay.png",
-        "assets/test_image_dont_starve_poster.png",
-        "assets/test_image_doom_demon.png",
-        "assets/test_image_doom_slayer.png",
-        "assets/test_image_elden_ring_gameplay.png",
-        "assets/test_image_elden_ring_poster.png",
-        "assets/test_image_hollow_knight_gameplay.png",
-        "assets/test_image_hollow_knight_poster.png",
-        "assets/test_image_titanfall_2_poster_1.png",
-        "assets/test_image_titanfall_2_poster_2.png",
-        "assets/test_image_train.jpg"
-    ];
-
-    if *index >= test_images.len() {
-        *index = 0;
-    }
+const DEFAULT_GLYPH_ATLAS: &str = "shaders/ascii/glyph_texture_v2-edge_(16x16x15).png";
+pub const DEFAULT_GLYPH_CHARS: &str = " .:-÷=+#X%@-/\\|";
+const GLYPH_CELL: (usize, usize) = (16, 16);
 
-    texture::Texture::load_file(test_images[*index]).expect("Failed to load texture")
-}
+// Also used by the text export of the ASCII effect
+pub const ASCII_CELL_SIZE: f32 = 8.0;
 
 pub fn load_shader(selected_shader: &SelectShader, size: (i32, i32)) -> shader_reader::ShaderReader {
     let mut shader: shader_reader::ShaderReader;
@@ -45,37 +30,70 @@ pub fn load_shader(selected_shader: &SelectShader, size: (i32, i32)) -> shader_r
             shader = shader_reader::ShaderReader::new("shaders/ascii/vertex_shader.glsl", "shaders/ascii/fragment_shader_with_edge.glsl");
             shader.bind();
 
-            shader.create_uniform("img_texture");
-            shader.set_int_uniform("img_texture", 0);
-
             shader.create_uniform("font_texture");
             shader.set_int_uniform("font_texture", 1);
 
+            // Cells and edges from the pre-pass
+            shader.create_uniform("cell_texture");
+            shader.set_int_uniform("cell_texture", 2);
+
+            shader.create_uniform("edge_texture");
+            shader.set_int_uniform("edge_texture", 3);
+
             shader.create_uniform("resolution");
             shader.set_vec2_f32_uniform("resolution", size.0 as f32, size.1 as f32);
 
             shader.create_uniform("cell_size");
-            shader.set_vec2_f32_uniform("cell_size", 8.0, 8.0);
-
-            shader.create_uniform("font_grid");
-            shader.set_vec2_i32_uniform("font_grid", 15, 1);
+            shader.set_vec2_f32_uniform("cell_size", ASCII_CELL_SIZE, ASCII_CELL_SIZE);
 
-            shader.create_uniform("glyph_count");
-            shader.set_int_uniform("glyph_count", 15);
-
-            shader.create_uniform("edge_threshold");
-            shader.set_float_uniform("edge_threshold", 0.8);
+            // font_grid and glyph_count come from the glyph atlas in use
         }
 
         SelectShader::Pixel => {
             shader = shader_reader::ShaderReader::new("shaders/pixel/vertex_shader.glsl", "shaders/pixel/fragment_shader.glsl");
             shader.bind();
 
+            // Framebuffer size, grid position and scale are set every frame
             shader.create_uniform("resolution");
             shader.set_vec2_f32_uniform("resolution", size.0 as f32, size.1 as f32);
 
-            shader.create_uniform("cell_size");
-            shader.set_vec2_f32_uniform("cell_size", 8.0, 8.0);
+            shader.create_uniform("grid_origin");
+            shader.set_vec2_f32_uniform("grid_origin", 0.0, 0.0);
+
+            shader.create_uniform("pixel_scale");
+            shader.set_float_uniform("pixel_scale", 1.0);
+
+            shader.create_uniform("palette_size");
+            shader.set_int_uniform("palette_size", 0);
+        }
+
+        SelectShader::Halftone => {
+            shader = shader_reader::ShaderReader::new("shaders/halftone/vertex_shader.glsl", "shaders/halftone/fragment_shader.glsl");
+            shader.bind();
+
+            // Cell grid and DPI are set every frame
+            shader.create_uniform("resolution");
+            shader.set_vec2_f32_uniform("resolution", size.0 as f32, size.1 as f32);
+        }
+
+        SelectShader::ColorVision => {
+            shader = shader_reader::ShaderReader::new("shaders/color_vision/vertex_shader.glsl", "shaders/color_vision/fragment_shader.glsl");
+            shader.bind();
+        }
+
+        SelectShader::Kuwahara => {
+            shader = shader_reader::ShaderReader::new("shaders/kuwahara/vertex_shader.glsl", "shaders/kuwahara/fragment_shader.glsl");
+            shader.bind();
+
+            shader.create_uniform("img_texture");
+            shader.set_int_uniform("img_texture", 0);
+
+            // Smoothed structure tensor for the anisotropic mode
+            shader.create_uniform("tensor_texture");
+            shader.set_int_uniform("tensor_texture", 1);
+
+            shader.create_uniform("resolution");
+            shader.set_vec2_f32_uniform("resolution", size.0 as f32, size.1 as f32);
         }
 
         SelectShader::Test => {
@@ -95,19 +113,87 @@ pub fn load_shader(selected_shader: &SelectShader, size: (i32, i32)) -> shader_r
     shader
 }
 
-pub fn rgb_downscale_by_factor(src: &[u8], w: usize, h: usize, f: usize) -> (Vec<u8>, usize, usize) {
-    let new_w = w / f;
-    let new_h = h / f;
-    let mut img = Vec::with_capacity(new_w * new_h * 3);
-
-    for y in 0..new_h {
-        for x in 0..new_w {
-            let i = ((y * f) * w + (x * f)) * 3;
-            img.push(src[i]);
-            img.push(src[i + 1]);
-            img.push(src[i + 2]);
-        }
+// Framebuffer size, scale and window position for the effects drawn on a cell grid (pixel, halftone).
+// Anchored grids start at the screen origin instead of the window's
+pub fn set_cell_grid_uniforms(shader: &mut shader_reader::ShaderReader, window: &window::Window, anchored: bool) {
+    let (w, h) = window.get_framebuffer_size();
+    shader.create_uniform("resolution");
+    shader.set_vec2_f32_uniform("resolution", w as f32, h as f32);
+
+    let scale = w as f32 / window.get_window_size().0.max(1) as f32;
+    let (x, y) = if anchored { window.get_window_pos() } else { (0, 0) };
+
+    shader.create_uniform("pixel_scale");
+    shader.set_float_uniform("pixel_scale", scale);
+
+    shader.create_uniform("grid_origin");
+    shader.set_vec2_f32_uniform("grid_origin", x as f32 * scale, y as f32 * scale);
+}
+
+// Runtime parameters of an effect, the "effect" group of the ParamSet
+pub fn effect_params(selected_shader: &SelectShader) -> Vec<Param> {
+    match selected_shader {
+        SelectShader::Ascii => vec![
+            Param::new("cell_average", 0.0, 0.0, 2.0, 1.0), // mean, median, dominant
+            Param::new("edge_detector", 0.0, 0.0, 2.0, 1.0), // Sobel, Scharr, difference of Gaussians
+            Param::new("edge_threshold", 0.8, 0.05, 4.0, 0.05),
+            Param::new("edge_hysteresis", 0.7, 0.0, 1.0, 0.05)
+        ],
+
+        SelectShader::Pixel => vec![
+            Param::new("pixel_size", 8.0, 1.0, 64.0, 1.0), // snapped to whole source texels
+            Param::new("outline", 0.0, 0.0, 1.0, 0.1),
+            Param::new("outline_threshold", 0.15, 0.02, 1.0, 0.01),
+            Param::new("palette_colors", 16.0, 2.0, MAX_PALETTE_COLORS as f32, 1.0) // used by the next palette lock
+        ],
+
+        SelectShader::Halftone => vec![
+            Param::new("halftone_mode", 0.0, 0.0, 1.0, 1.0), // CMYK, newspaper
+            Param::new("dot_shape", 0.0, 0.0, 2.0, 1.0), // round, line, square
+            Param::new("lpi", 16.0, 4.0, 100.0, 1.0), // lines per inch of the window
+            Param::new("angle_c", 15.0, 0.0, 180.0, 5.0),
+            Param::new("angle_m", 75.0, 0.0, 180.0, 5.0),
+            Param::new("angle_y", 0.0, 0.0, 180.0, 5.0),
+            Param::new("angle_k", 45.0, 0.0, 180.0, 5.0), // also the screen of the newspaper mode
+            Param::new("paper_tint", 0.3, 0.0, 1.0, 0.05)
+        ],
+
+        SelectShader::ColorVision => vec![
+            Param::new("cvd_mode", 0.0, 0.0, 3.0, 1.0), // simulate, daltonize, high contrast, inverted luminance
+            Param::new("cvd_type", 0.0, 0.0, 2.0, 1.0), // protan, deutan, tritan
+            Param::new("severity", 1.0, 0.0, 1.0, 0.1)
+        ],
+
+        SelectShader::Kuwahara => vec![
+            Param::new("kuwahara_mode", 2.0, 0.0, 2.0, 1.0), // basic, generalized, anisotropic
+            Param::new("kernel_radius", 6.0, 2.0, 12.0, 1.0),
+            Param::new("sectors", 8.0, 3.0, 12.0, 1.0), // generalized and anisotropic
+            Param::new("sharpness", 8.0, 1.0, 18.0, 1.0)
+        ],
+
+        _ => Vec::new()
     }
+}
+
+// Hand-made atlas without a ramp, otherwise the ramp rasterized from the font found by find_font
+pub fn load_glyph_atlas(ramp: Option<&GlyphRamp>) -> Result<(GlyphAtlas, Texture), String> {
+    let atlas = match ramp {
+        None => {
+            println!("Glyph atlas: {}", DEFAULT_GLYPH_ATLAS);
+
+            GlyphAtlas::load_png(Path::new(DEFAULT_GLYPH_ATLAS), DEFAULT_GLYPH_CHARS)?
+        }
+
+        Some(ramp) => {
+            let font = find_font().ok_or("No font found, set SHADER_WINDOW_FONT or put a TTF/OTF into the fonts directory")?;
+            println!("Glyph atlas: {} from {}", ramp.name, font.display());
+
+            GlyphAtlas::build(&font, &ramp.chars, GLYPH_CELL)?
+        }
+    };
+
+    let texture = Texture::new(atlas.frame.width as i32, atlas.frame.height as i32, gl::RGBA8)?;
+    texture.upload_frame(&atlas.frame)?;
 
-    (img, new_w, new_h)
+    Ok((atlas, texture))
 }
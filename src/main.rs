@@ -1,16 +1,30 @@
-use std::ptr;
+use std::path::Path;
 use std::time::Instant;
-use std::sync::mpsc;
+use std::sync::{ mpsc, Arc };
 use glfw::Key;
 use glfw::ffi::glfwShowWindow;
-use tokio::sync::broadcast;
-use mozjpeg::{ Compress, ColorSpace };
 
 mod graphics;
 mod capture;
 mod select_shader;
 mod select_mode;
 mod web_socket;
+mod frame;
+mod scaling;
+mod share_hub;
+mod encoder;
+mod share_encoder;
+mod adaptive_quality;
+mod recording;
+mod screenshot;
+mod source;
+mod playlist;
+mod cube_lut;
+mod effect_params;
+mod glyph_atlas;
+mod ascii_export;
+mod terminal;
+mod palette;
 mod help_functions;
 
 use graphics::*;
@@ -18,9 +32,43 @@ use capture::*;
 use select_shader::SelectShader;
 use select_mode::SelectMode;
 use web_socket::start_server;
+use share_hub::ShareHub;
+use share_encoder::{ ShareFrame, spawn_encoder_thread };
+use scaling::ScaleFilter;
+use recording::{ RecordFormat, Recorder };
+use screenshot::save_screenshot;
+use playlist::Playlist;
+use tone_mapper::{ ToneMap, ToneMapper };
+use color_grader::ColorGrader;
+use ascii_prepass::AsciiPrepass;
+use structure_tensor::StructureTensor;
+use magnifier::Magnifier;
+use cube_lut::{ CubeLut, find_luts };
+use effect_params::{ Param, ParamSet };
+use glyph_atlas::read_ramps;
+use palette::kmeans_palette;
+use ascii_export::save_ascii_export;
 use help_functions::*;
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // The playlist defaults to every image in assets and the generated patterns
+    let default_specs = |args: Vec<String>| if args.is_empty() { vec!["assets".to_string(), "patterns".to_string()] } else { args };
+
+    // --terminal renders the ASCII effect into the console without a window, of the playlist or with --capture of the screen
+    if let Some(i) = args.iter().position(|arg| arg == "--terminal") {
+        args.remove(i);
+
+        let capture = args.iter().position(|arg| arg == "--capture").map(|i| args.remove(i)).is_some();
+
+        if let Err(e) = terminal::run(&default_specs(args), capture) {
+            println!("{}", e);
+        }
+
+        return;
+    }
+
     let mut window = window::Window::new(1280, 720, "Window");
     window.init_gl();
     window.set_fps(1);
@@ -29,89 +77,98 @@ fn main() {
         glfwShowWindow(window.get_window_ptr());
     }
 
-    let mut vao = 0;
-    let mut vbo = 0;
-    let mut ibo = 0;
-
-    let vertices: Vec<f32> = vec![
-        // positions   // texture coords
-        1.0,  1.0,     1.0, 0.0,  // top right
-        1.0, -1.0,     1.0, 1.0,  // bottom right
-        -1.0, -1.0,    0.0, 1.0,  // bottom left
-        -1.0,  1.0,    0.0, 0.0   // top left
-    ];
-
-    let indices: Vec<u32> = vec![
-        0, 1, 3,  // first triangle
-        1, 2, 3   // second triangle
-    ];
-    
-    unsafe {
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
-        gl::GenBuffers(1, &mut ibo);
-        
-        gl::BindVertexArray(vao);
-        
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * std::mem::size_of::<f32>()) as isize, vertices.as_ptr() as *const _, gl::STATIC_DRAW);
-        
-        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ibo);
-        gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * std::mem::size_of::<u32>()) as isize, indices.as_ptr() as *const _, gl::STATIC_DRAW);
-        
-        gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 4 * std::mem::size_of::<f32>() as i32, ptr::null());
-        gl::EnableVertexAttribArray(0);
-        
-        gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 4 * std::mem::size_of::<f32>() as i32, (2 * std::mem::size_of::<f32>()) as *const _);
-        gl::EnableVertexAttribArray(1);
-        
-        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-        gl::BindVertexArray(0);
-    }
+    let quad = quad::Quad::new();
 
     // Setup web socket for window sharing
     let mut share_window = false;
-    let (tx, _rx) = broadcast::channel::<Vec<u8>>(4); // FIX: connection breaks when app window is minimised
-    start_server(tx.clone());
-    let mut frame: Vec<u8>;
+    let share_hub = Arc::new(ShareHub::new()); // FIX: connection breaks when app window is minimised
+    start_server(share_hub.clone());
+    let mut readback = readback::PixelReadback::new(3, 4); // triple buffered RGBA
+    let mut gpu_convert = false;
+    let mut yuv_converter = yuv_converter::YuvConverter::new();
 
     // Setup encoder thread
-    let (frame_tx, frame_rx) = mpsc::sync_channel::<(Vec<u8>, i32, i32)>(1); // bound of 1 = drop if busy
-    let tx_clone = tx.clone();
-
-    std::thread::spawn(move || {
-        while let Ok((frame, w, h)) = frame_rx.recv() {
-            let start = Instant::now();
-
-            // Reduce resolution before encoding
-            let (frame, w, h) = rgb_downscale_by_factor(&frame, w as usize, h as usize, 2);
+    let mut share_filter = ScaleFilter::Area;
+    let (frame_tx, frame_rx) = mpsc::sync_channel::<(Vec<ShareFrame>, ScaleFilter)>(1); // bound of 1 = drop if busy
+    spawn_encoder_thread(share_hub.clone(), frame_rx, readback.recycler(), yuv_converter.recycler());
 
-            // Encode RGB frame to jpeg
-            let mut comp = Compress::new(ColorSpace::JCS_RGB);
-            comp.set_fastest_defaults();
-            comp.set_size(w as usize, h as usize);
-            comp.set_quality(100.0); // 60 is recommended but with downscale, text becomes completely unreadable and helped with maybe 2-5ms
-            //comp.set_chroma_sampling_pixel_sizes((2,2), (2,2)); // 4:2:0 chroma subsampling helped with maybe 2-5ms but worse quality
-            let mut comp = comp.start_compress(Vec::new()).unwrap();
-            comp.write_scanlines(&frame).unwrap();
-            let compressed = comp.finish().unwrap();
+    // Setup recording, uses the same readback as sharing
+    let mut record_format = RecordFormat::MjpegAvi;
+    let mut recorder: Option<Recorder> = None;
+    let mut screenshot: Option<bool> = None; // requested this frame, with or without the side-by-side composite
+    let mut ascii_export = false;
 
-            let _ = tx_clone.send(compressed);
-            println!("encode: {:?}", start.elapsed());
-        }
-    });
-    
     // Setup screen capture
     let mut select_mode = SelectMode::Image;
     capture_settings(window.get_window_ptr());
     let mut capture = ScreenCapture::new();
 
-    // Load image as a texture
-    let mut texture_idx: usize = 0;
-    let mut texture = load_test_image(&mut texture_idx);
-    window.set_window_size(texture.get_texture_size());
+    // Load images from the directories, file name patterns or list files given as arguments (animated images play their frames)
+    let playlist_specs = default_specs(args);
+    let mut playlist = match Playlist::from_specs(&playlist_specs) {
+        Ok(playlist) => playlist,
+
+        Err(e) => {
+            println!("Failed to create image playlist: {}", e);
+            return;
+        }
+    };
+
+    // Unreadable entries are skipped, so this only fails when none of them can be shown
+    let Some(mut source) = playlist.load_current() else {
+        println!("No readable image in the playlist: {}", playlist_specs.join(", "));
+        return;
+    };
+
+    window.set_window_size(source.get_size());
+
+    // Tone mapping of HDR sources, effects then draw to a float target first
+    let mut tone_map = ToneMap::Off;
+    let mut tone_mapper = ToneMapper::new();
+
+    // Runtime parameters, selected and changed with the arrow keys
+    let mut params = ParamSet::new();
+    params.set_group("effect", effect_params(&SelectShader::None));
+    params.set_group("lut", vec![
+        Param::new("lut_strength", 1.0, 0.0, 1.0, 0.05),
+        Param::new("lut_tetrahedral", 0.0, 0.0, 1.0, 1.0)
+    ]);
+
+    params.set_group("lens", vec![
+        Param::new("lens_zoom", 4.0, 1.0, 16.0, 1.0),
+        Param::new("lens_radius", 120.0, 20.0, 600.0, 10.0),
+        Param::new("lens_smooth", 0.0, 0.0, 1.0, 1.0),
+        Param::new("lens_grid", 1.0, 0.0, 1.0, 1.0),
+        Param::new("lens_follow", 1.0, 0.0, 1.0, 1.0) // 0 keeps the lens where it is
+    ]);
+
+    // Color grading with the .cube LUTs of the luts directory, off until one is picked
+    let mut color_grader = ColorGrader::new();
+    let mut lut_index: Option<usize> = None;
+
+    // Zoom lens following the cursor, over every effect
+    let mut magnifier = Magnifier::new();
+    let mut magnify = false;
+    let mut lens_pos = (0.0, 0.0);
+
+    // Glyphs of the ASCII effect, the hand-made atlas or one of the ramps rasterized from a font
+    let glyph_ramps = read_ramps(Path::new("shaders/ascii/glyph_textures.txt")).unwrap_or_else(|e| {
+        println!("{}", e);
+        Vec::new()
+    });
+
+    let mut glyph_ramp_index: Option<usize> = None;
+    let (mut glyph_atlas, mut glyph_texture) = load_glyph_atlas(None).expect("Failed to load glyph atlas"); // FIX: load only for ascii
+
+    // Colors of the pixel effect, locked from a frame with k-means
+    let mut palette: Vec<[f32; 3]> = Vec::new();
+    let mut palette_lock = false; // requested this frame
 
-    let glyph_texture = texture::Texture::load_file("shaders/ascii/glyph_texture_v2-edge_(16x16x15).png").expect("Failed to load texture"); // FIX: load only for ascii
+    // Cell colors and edges of the ASCII effect, computed at cell resolution before it is drawn
+    let mut ascii_prepass = AsciiPrepass::new();
+
+    // Edge directions for the anisotropic Kuwahara filter
+    let mut structure_tensor = StructureTensor::new();
 
     // Load selected shaders
     let mut selected_shader: SelectShader = SelectShader::None;
@@ -135,9 +192,9 @@ fn main() {
                     // Toggle capture and image modes
                     select_mode = select_mode.next();
 
-                    if select_mode == SelectMode::Image {
-                        texture = load_test_image(&mut texture_idx);
-                        window.set_window_size(texture.get_texture_size());
+                    if select_mode == SelectMode::Image && let Some(next) = playlist.load_current() {
+                        source = next;
+                        window.set_window_size(source.get_size());
                     }
                 }
 
@@ -151,14 +208,114 @@ fn main() {
                     share_window = !share_window;
                     println!("Toggle share server: {}", share_window)
                 }
+
+                if window.is_key_released(Key::G) {
+                    // Toggle downscale and YUV conversion of the shared window on the GPU
+                    gpu_convert = !gpu_convert;
+                    println!("GPU share conversion: {}", gpu_convert);
+                }
+
+                if window.is_key_released(Key::F) {
+                    // Next downscale filter of the shared window
+                    share_filter = share_filter.next();
+                    println!("Share downscale filter: {:?}", share_filter);
+                }
+
+                if window.is_key_released(Key::R) && !window.is_key_pressed(Key::N) {
+                    // Start/stop recording of the app window
+                    recorder = match recorder.take() {
+                        Some(recorder) => {
+                            recorder.stop();
+                            None
+                        }
+
+                        None => Recorder::start(record_format).map_err(|e| println!("Failed to start recording: {}", e)).ok()
+                    };
+                }
+
+                if window.is_key_released(Key::Space) {
+                    // Pause/resume an animated image
+                    source.toggle_pause();
+                }
+
+                if window.is_key_released(Key::Period) {
+                    // Show the next frame of an animated image and stay paused
+                    source.step();
+                }
+
+                if window.is_key_released(Key::L) && !window.is_key_pressed(Key::N) {
+                    // Toggle looping of an animated image
+                    source.toggle_loop();
+                }
+
+                if window.is_key_released(Key::Comma) {
+                    // Toggle between the timing of the file and one frame per rendered frame
+                    source.toggle_lock_step();
+                }
+
+                if window.is_key_released(Key::A) && !window.is_key_pressed(Key::N) {
+                    // Save the ASCII effect output as text, ANSI and HTML
+                    ascii_export = true;
+                }
+
+                if window.is_key_released(Key::M) {
+                    // Toggle the magnifier
+                    magnify = !magnify;
+                    println!("Magnifier: {}", magnify);
+                }
+
+                if window.is_key_released(Key::K) {
+                    // Lock the pixel effect to a palette of the current frame, or unlock it
+                    palette_lock = true;
+                }
+
+                if window.is_key_released(Key::Up) {
+                    // Previous runtime parameter
+                    params.select(-1);
+                }
+
+                if window.is_key_released(Key::Down) {
+                    // Next runtime parameter
+                    params.select(1);
+                }
+
+                if window.is_key_released(Key::Left) {
+                    // Decrease the selected parameter
+                    params.adjust(-1.0);
+                }
+
+                if window.is_key_released(Key::Right) {
+                    // Increase the selected parameter
+                    params.adjust(1.0);
+                }
+
+                if window.is_key_released(Key::X) {
+                    // Screenshot of the output and the source, with Shift also side by side
+                    screenshot = Some(window.is_key_pressed(Key::LeftShift));
+                }
                 
+                if window.is_key_pressed(Key::P) && window.is_key_released(Key::I) {
+                    // Previous test image
+                    if select_mode == SelectMode::Image && let Some(previous) = playlist.load_previous() {
+                        source = previous;
+                        window.set_window_size(source.get_size());
+                    }
+                }
+
                 if window.is_key_pressed(Key::N) {
                     if window.is_key_released(Key::I) {
                         // Next test image
-                        if select_mode == SelectMode::Image {
-                            texture_idx += 1;
-                            texture = load_test_image(&mut texture_idx);
-                            window.set_window_size(texture.get_texture_size());
+                        if select_mode == SelectMode::Image && let Some(next) = playlist.load_next() {
+                            source = next;
+                            window.set_window_size(source.get_size());
+                        }
+                    }
+
+                    if window.is_key_released(Key::R) {
+                        // Random test image
+                        if select_mode == SelectMode::Image && let Some(next) = playlist.load_random() {
+                            source = next;
+                            window.set_window_size(source.get_size());
                         }
                     }
 
@@ -166,16 +323,92 @@ fn main() {
                         // Next shader/effect
                         selected_shader = selected_shader.next();
                         shader = load_shader(&selected_shader, window.get_window_size());
+                        params.set_group("effect", effect_params(&selected_shader));
+                    }
+
+                    if window.is_key_released(Key::V) {
+                        // Next recording format, used from the next recording on
+                        record_format = record_format.next();
+                        println!("Recording format: {:?}", record_format);
+                    }
+
+                    if window.is_key_released(Key::T) {
+                        // Next tone mapping operator
+                        tone_map = tone_map.next();
+                        println!("Tone mapping: {:?}", tone_map);
+                    }
+
+                    if window.is_key_released(Key::A) {
+                        // Next glyph ramp of the ASCII effect, back to the hand-made atlas after the last one
+                        let next = glyph_ramp_index.map_or(0, |i| i + 1);
+                        glyph_ramp_index = (next < glyph_ramps.len()).then_some(next);
+
+                        match load_glyph_atlas(glyph_ramp_index.map(|i| &glyph_ramps[i])) {
+                            Ok((atlas, texture)) => (glyph_atlas, glyph_texture) = (atlas, texture),
+                            Err(e) => println!("{}", e)
+                        }
+                    }
+
+                    if window.is_key_released(Key::L) {
+                        // Next LUT, the directory is read again so new files show up. Grading is off after the last one
+                        let luts = find_luts(Path::new("luts"));
+                        let next = lut_index.map_or(0, |i| i + 1);
+                        lut_index = (next < luts.len()).then_some(next);
+
+                        let result = match lut_index {
+                            Some(i) => CubeLut::load(&luts[i]).and_then(|lut| {
+                                println!("LUT: {} {}", luts[i].display(), lut.title);
+                                color_grader.set_lut(Some(&lut))
+                            }),
+
+                            None => {
+                                println!("LUT: off");
+                                color_grader.set_lut(None)
+                            }
+                        };
+
+                        if let Err(e) = result {
+                            println!("{}", e);
+                            let _ = color_grader.set_lut(None);
+                        }
                     }
                 }
             }
         }
 
-        if select_mode == SelectMode::ScreenCapture {
-            let (w, h) = window.get_window_size();
-            let (x, y) = window.get_window_pos();
-            
-            texture = capture.get_frame((w as usize, h as usize), (x as usize, y as usize)).clone();
+        // Dropped images are shown next, in image mode
+        let dropped_files = window.take_dropped_files();
+
+        if !dropped_files.is_empty() && let Some(next) = playlist.insert(&dropped_files) {
+            select_mode = SelectMode::Image;
+            source = next;
+            window.set_window_size(source.get_size());
+        }
+
+        let linear_source = select_mode == SelectMode::Image && source.is_linear();
+
+        let texture = match select_mode {
+            SelectMode::ScreenCapture => {
+                let (w, h) = window.get_window_size();
+                let (x, y) = window.get_window_pos();
+
+                capture.get_frame((w as usize, h as usize), (x as usize, y as usize))
+            }
+
+            SelectMode::Image => source.update(dt)
+        };
+
+        if palette_lock {
+            palette_lock = false;
+
+            if palette.is_empty() {
+                let colors = params.get("palette_colors").unwrap_or(16.0) as usize;
+                palette = kmeans_palette(&texture.download_frame(), colors);
+                println!("Palette locked: {} colors", palette.len());
+            } else {
+                palette.clear();
+                println!("Palette unlocked");
+            }
         }
 
         // Update shaders and textures
@@ -189,19 +422,66 @@ fn main() {
             }
 
             SelectShader::Ascii => {
-                shader.bind();
-
                 let (w, h) = window.get_window_size();
+                ascii_prepass.run(&quad, texture, (w, h), (ASCII_CELL_SIZE, ASCII_CELL_SIZE), window.get_framebuffer_size(), &params);
+
+                shader.bind();
                 shader.create_uniform("resolution");
                 shader.set_vec2_f32_uniform("resolution", w as f32, h as f32);
 
+                shader.create_uniform("font_grid");
+                shader.set_vec2_i32_uniform("font_grid", glyph_atlas.grid.0, glyph_atlas.grid.1);
+
+                shader.create_uniform("glyph_count");
+                shader.set_int_uniform("glyph_count", glyph_atlas.glyph_count);
+
                 glyph_texture.bind(gl::TEXTURE1);
+                ascii_prepass.bind_textures(gl::TEXTURE2, gl::TEXTURE3);
             }
 
             SelectShader::Pixel => {
                 shader.bind();
+                params.apply("effect", &mut shader);
+
+                // In capture mode the grid is anchored to the screen, so it doesn't crawl when the window moves
+                set_cell_grid_uniforms(&mut shader, &window, select_mode == SelectMode::ScreenCapture);
+
+                shader.create_uniform("palette_size");
+                shader.set_int_uniform("palette_size", palette.len() as i32);
+
+                for (i, color) in palette.iter().enumerate() {
+                    let name = format!("palette[{}]", i);
+                    shader.create_uniform(&name);
+                    shader.set_vec3_f32_uniform(&name, *color);
+                }
+            }
+
+            SelectShader::Halftone => {
+                shader.bind();
+                params.apply("effect", &mut shader);
+                set_cell_grid_uniforms(&mut shader, &window, select_mode == SelectMode::ScreenCapture);
+
+                let dpi = window.get_dpi();
+                shader.create_uniform("dpi");
+                shader.set_float_uniform("dpi", dpi);
+            }
 
+            SelectShader::ColorVision => {
+                shader.bind();
+                params.apply("effect", &mut shader);
+            }
+
+            SelectShader::Kuwahara => {
                 let (w, h) = window.get_window_size();
+
+                if params.get("kuwahara_mode").is_some_and(|mode| mode.round() == 2.0) {
+                    structure_tensor.run(&quad, texture, (w, h), window.get_framebuffer_size());
+                    structure_tensor.bind_texture(gl::TEXTURE1);
+                }
+
+                shader.bind();
+                params.apply("effect", &mut shader);
+
                 shader.create_uniform("resolution");
                 shader.set_vec2_f32_uniform("resolution", w as f32, h as f32);
             }
@@ -218,25 +498,116 @@ fn main() {
 
         texture.bind(gl::TEXTURE0);
 
+        let window_size = window.get_framebuffer_size();
+
+        if magnify {
+            if params.get("lens_follow").is_some_and(|follow| follow > 0.5) {
+                lens_pos = window.get_cursor_pos();
+            }
+
+            magnifier.begin(window_size);
+        }
+
+        if color_grader.is_active() {
+            color_grader.begin(window_size);
+        }
+
+        // Linear sources always go through the pass, which also does their sRGB encode
+        let tone_pass = tone_map != ToneMap::Off || linear_source;
+
+        if tone_pass {
+            tone_mapper.begin(window_size);
+        }
+
         // Draw to window
         unsafe {
             gl::ClearColor(0.0, 0.0, 0.0, 0.0);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        quad.draw();
+
+        if tone_pass {
+            tone_mapper.finish(&quad, window_size, tone_map, linear_source);
+        }
+
+        if color_grader.is_active() {
+            color_grader.finish(&quad, window_size, &params);
+        }
+
+        if magnify {
+            let pixel_scale = window_size.0 as f32 / window.get_window_size().0.max(1) as f32;
+            magnifier.finish(&quad, window_size, lens_pos, pixel_scale, &params);
+        }
+
+        if let Some(composite) = screenshot.take() {
+            let (w, h) = window.get_framebuffer_size();
+
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            }
+
+            save_screenshot(readback::read_pixels(w, h), texture.download_frame(), format!("{:?}", selected_shader), params.group_values("effect"), shader.get_uniform_values(), composite);
+        }
+
+        if ascii_export {
+            ascii_export = false;
 
-            gl::BindVertexArray(vao);
-            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
-            gl::BindVertexArray(0);
+            // Read back from the pre-pass, so the export has the glyphs and colors on screen
+            if selected_shader == SelectShader::Ascii && let Some(grid) = ascii_prepass.download_grid(glyph_atlas.glyph_count) {
+                save_ascii_export(grid, glyph_atlas.chars.clone());
+            } else {
+                println!("ASCII export needs the ASCII effect");
+            }
         }
 
-        if share_window && tx.receiver_count() > 0 {
-            // Load pixels from the window into a variable
-            let (w, h) = window.get_window_size();
-            frame = window.read_pixels(); // Vec<u8> RGB
+        let sharing = share_window && share_hub.has_viewers();
+        let streams = if sharing { share_hub.active_streams() } else { Vec::new() };
+
+        // With GPU conversion the JPEG 4:2:0 streams take the planes, all other streams still need the RGBA readback
+        let gpu_scale = streams.iter().filter(|s| gpu_convert && s.format.takes_yuv420()).map(|s| s.scale_percent).max();
+        let share_rgba = streams.iter().any(|s| !(gpu_convert && s.format.takes_yuv420()));
+        let mut share_frames = Vec::new();
+
+        // Queue an asynchronous read of this frame, pick up an earlier one the GPU has finished
+        if let Some(scale) = gpu_scale {
+            // Converted at the largest scale in use, smaller streams are scaled down from it
+            yuv_converter.request(&quad, window_size, scale);
 
-            // Try_send - if the encoder thread is still busy, drop the frame
-            let _ = frame_tx.try_send((frame, w, h));
+            if let Some(planes) = yuv_converter.poll() {
+                share_frames.push(ShareFrame::Yuv420(planes, scale));
+            }
+        }
+
+        if share_rgba || recorder.is_some() {
+            readback.request(window_size.0, window_size.1);
+
+            if let Some((frame, requested)) = readback.poll() {
+                match &mut recorder {
+                    Some(recorder) if share_rgba => {
+                        recorder.push(frame.clone(), requested);
+                        share_frames.push(ShareFrame::Rgba(frame, gpu_convert));
+                    }
+
+                    Some(recorder) => recorder.push(frame, requested),
+
+                    None => {
+                        share_frames.push(ShareFrame::Rgba(frame, gpu_convert));
+                    }
+                }
+            }
+        }
+
+        // Try_send - if the encoder thread is still busy, drop the frames
+        if !share_frames.is_empty() {
+            let _ = frame_tx.try_send((share_frames, share_filter));
         }
         
         window.update();
     }
+
+    // Finish the file of a recording that was still running
+    if let Some(recorder) = recorder {
+        recorder.stop();
+    }
 }
@@ -0,0 +1,72 @@
+use crate::frame::Frame;
+
+pub const MAX_PALETTE_COLORS: usize = 32; // size of the palette array in shaders/pixel/fragment_shader.glsl
+
+const MAX_SAMPLES: usize = 16384;
+const ITERATIONS: usize = 16;
+
+// Most representative colors of the frame by k-means, 0..1 sRGB sorted from dark to bright
+pub fn kmeans_palette(frame: &Frame, colors: usize) -> Vec<[f32; 3]> {
+    let colors = colors.clamp(1, MAX_PALETTE_COLORS);
+    let step = (frame.width * frame.height).div_ceil(MAX_SAMPLES).max(1);
+
+    let samples: Vec<[f32; 3]> = frame.pixels.chunks_exact(frame.channels).step_by(step)
+        .map(|px| [0, 1, 2].map(|c| px[c.min(frame.channels - 1)] as f32 / 255.0))
+        .collect();
+
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    // Start from evenly spaced luminance quantiles so the result is the same for the same frame
+    let mut sorted = samples.clone();
+    sorted.sort_by(|a, b| luminance(*a).total_cmp(&luminance(*b)));
+
+    let mut centers: Vec<[f32; 3]> = (0..colors).map(|i| sorted[(2 * i + 1) * sorted.len() / (2 * colors)]).collect();
+    centers.dedup();
+
+    for _ in 0..ITERATIONS {
+        let mut sums = vec![([0.0f32; 3], 0usize); centers.len()];
+
+        for sample in &samples {
+            let (sum, count) = &mut sums[nearest(&centers, *sample)];
+
+            for c in 0..3 {
+                sum[c] += sample[c];
+            }
+
+            *count += 1;
+        }
+
+        let mut moved = false;
+
+        for (center, (sum, count)) in centers.iter_mut().zip(sums) {
+            if count == 0 {
+                continue;
+            }
+
+            let mean = sum.map(|v| v / count as f32);
+            moved |= distance(*center, mean) > 1e-6;
+            *center = mean;
+        }
+
+        if !moved {
+            break;
+        }
+    }
+
+    centers.sort_by(|a, b| luminance(*a).total_cmp(&luminance(*b)));
+    centers
+}
+
+fn nearest(centers: &[[f32; 3]], color: [f32; 3]) -> usize {
+    (0..centers.len()).min_by(|&a, &b| distance(centers[a], color).total_cmp(&distance(centers[b], color))).unwrap_or(0)
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+fn luminance(c: [f32; 3]) -> f32 {
+    c[0] * 0.299 + c[1] * 0.587 + c[2] * 0.114
+}
@@ -0,0 +1,162 @@
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use crate::source::{ self, FrameSource, Pattern };
+
+// Ordered list of image files and generated patterns, built from directories, file name patterns or list files
+pub struct Playlist {
+    entries: Vec<PathBuf>,
+    index: usize,
+    random_state: u64
+}
+
+impl Playlist {
+    // Entries of every spec in order. Spec examples: "assets", "assets/*.png", "assets/test_image_doom_?????.png",
+    // "playlist.txt", "patterns" (every generated pattern), "pattern:zone-plate" or a single file
+    pub fn from_specs(specs: &[String]) -> Result<Playlist, String> {
+        let mut entries = Vec::new();
+
+        for spec in specs {
+            entries.extend(entries_from(Path::new(spec))?);
+        }
+
+        if entries.is_empty() {
+            return Err(format!("No images found in {}", specs.join(", ")));
+        }
+
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64;
+
+        Ok(Playlist { entries, index: 0, random_state: seed | 1 })
+    }
+
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    pub fn load_current(&mut self) -> Option<Box<dyn FrameSource>> {
+        self.load_from(self.index, 1, self.entries.len())
+    }
+
+    pub fn load_next(&mut self) -> Option<Box<dyn FrameSource>> {
+        self.load_from(self.index + 1, 1, self.entries.len())
+    }
+
+    pub fn load_previous(&mut self) -> Option<Box<dyn FrameSource>> {
+        self.load_from(self.index + self.entries.len() - 1, -1, self.entries.len())
+    }
+
+    // Any entry except the current one
+    pub fn load_random(&mut self) -> Option<Box<dyn FrameSource>> {
+        // xorshift64
+        self.random_state ^= self.random_state << 13;
+        self.random_state ^= self.random_state >> 7;
+        self.random_state ^= self.random_state << 17;
+
+        let offset = 1 + self.random_state as usize % self.entries.len().saturating_sub(1).max(1);
+
+        self.load_from(self.index + offset, 1, self.entries.len())
+    }
+
+    // Dropped files and directories are inserted after the current entry and the first readable one is loaded
+    pub fn insert(&mut self, paths: &[PathBuf]) -> Option<Box<dyn FrameSource>> {
+        let mut added = Vec::new();
+
+        for path in paths {
+            match entries_from(path) {
+                Ok(entries) => added.extend(entries),
+                Err(e) => println!("{}", e)
+            }
+        }
+
+        let count = added.len();
+        let at = (self.index + 1).min(self.entries.len());
+        self.entries.splice(at..at, added);
+
+        self.load_from(at, 1, count)
+    }
+
+    // Tries up to attempts entries from start in the step direction, unreadable files are skipped
+    fn load_from(&mut self, start: usize, step: isize, attempts: usize) -> Option<Box<dyn FrameSource>> {
+        let len = self.entries.len();
+
+        for attempt in 0..attempts {
+            let index = (start as isize + step * attempt as isize).rem_euclid(len as isize) as usize;
+            let path = &self.entries[index];
+
+            match source::open(&path.to_string_lossy()) {
+                Ok(source) => {
+                    println!("Image {}/{}: {}", index + 1, len, path.display());
+                    self.index = index;
+
+                    return Some(source);
+                }
+
+                Err(e) => println!("Skipping {}: {}", path.display(), e)
+            }
+        }
+
+        None
+    }
+}
+
+fn entries_from(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path == Path::new("patterns") {
+        return Ok(Pattern::ALL.iter().map(|p| PathBuf::from(format!("pattern:{}", p.name()))).collect());
+    }
+
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+
+    if name.contains(['*', '?']) {
+        // Pattern only in the file name, e.g. assets/*.png
+        let directory = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let mut entries = read_directory(directory)?;
+        entries.retain(|entry| entry.file_name().and_then(|n| n.to_str()).is_some_and(|n| wildcard_match(name.as_bytes(), n.as_bytes())));
+
+        return Ok(entries);
+    }
+
+    if path.is_dir() {
+        let mut entries = read_directory(path)?;
+        entries.retain(|entry| source::is_supported(entry));
+
+        return Ok(entries);
+    }
+
+    match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("txt") | Some("m3u") | Some("m3u8") => read_list(path),
+        _ => Ok(vec![path.to_path_buf()])
+    }
+}
+
+// Files of a directory sorted by name
+fn read_directory(directory: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(directory).map_err(|e| format!("Failed to read {}: {}", directory.display(), e))?;
+
+    let mut files: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.is_file()).collect();
+    files.sort();
+
+    Ok(files)
+}
+
+// One path per line, relative to the list file, or a generated pattern. Empty lines and lines starting with # are ignored
+fn read_list(list: &Path) -> Result<Vec<PathBuf>, String> {
+    let content = std::fs::read_to_string(list).map_err(|e| format!("Failed to read {}: {}", list.display(), e))?;
+    let base = list.parent().unwrap_or(Path::new("."));
+
+    Ok(content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| if line.starts_with("pattern:") { PathBuf::from(line) } else { base.join(line) })
+        .collect())
+}
+
+// * matches any number of characters, ? exactly one
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => wildcard_match(&pattern[1..], name) || (!name.is_empty() && wildcard_match(pattern, &name[1..])),
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false
+    }
+}
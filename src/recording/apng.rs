@@ -0,0 +1,163 @@
+use std::fs::File;
+use std::io::{ BufWriter, Seek, SeekFrom, Write };
+use std::path::{ Path, PathBuf };
+
+use crate::encoder::encode_png;
+use crate::frame::Frame;
+use crate::recording::FrameWriter;
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+// Animated PNG assembled from the chunks of single PNG frames. The first frame is the regular image,
+// so viewers without APNG support still show it
+pub struct ApngWriter {
+    file: BufWriter<File>,
+    path: PathBuf,
+    pending: Option<(Vec<u8>, f32)>, // encoded frame waiting for the next timestamp to know its delay
+    last_delay: f32,
+    sequence: u32, // shared by fcTL and fdAT chunks
+    frames: u32,
+    actl_offset: u64
+}
+
+impl ApngWriter {
+    pub fn create(path: &Path) -> Result<ApngWriter, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+
+        Ok(ApngWriter { file: BufWriter::new(file), path: path.to_path_buf(), pending: None, last_delay: 0.1, sequence: 0, frames: 0, actl_offset: 0 })
+    }
+
+    fn write_chunk(&mut self, chunk_type: &[u8; 4], data: &[u8]) -> Result<(), String> {
+        let mut crc = crc32fast::Hasher::new();
+        crc.update(chunk_type);
+        crc.update(data);
+
+        self.file.write_all(&(data.len() as u32).to_be_bytes())
+            .and_then(|_| self.file.write_all(chunk_type))
+            .and_then(|_| self.file.write_all(data))
+            .and_then(|_| self.file.write_all(&crc.finalize().to_be_bytes()))
+            .map_err(|e| format!("Failed to write apng chunk: {}", e))
+    }
+
+    fn animation_control(&self) -> Vec<u8> {
+        // Frame count, then 0 plays = loop forever
+        [self.frames.to_be_bytes(), 0u32.to_be_bytes()].concat()
+    }
+
+    fn write_pending(&mut self, delay: f32) -> Result<(), String> {
+        let Some((png, _)) = self.pending.take() else {
+            return Ok(());
+        };
+
+        let chunks = png_chunks(&png)?;
+        let (_, header) = chunks.iter().find(|(chunk_type, _)| chunk_type == b"IHDR").ok_or("Frame png has no header")?;
+
+        if self.frames == 0 {
+            self.file.write_all(&SIGNATURE).map_err(|e| format!("Failed to write apng signature: {}", e))?;
+            self.write_chunk(b"IHDR", header)?;
+
+            // Frame count is not known yet, patched in finish
+            self.actl_offset = self.file.stream_position().map_err(|e| format!("Failed to write apng: {}", e))?;
+            self.write_chunk(b"acTL", &self.animation_control())?;
+
+            // Color information like sRGB has to come before the image data
+            for (chunk_type, data) in chunks.iter().take_while(|(chunk_type, _)| chunk_type != b"IDAT") {
+                if chunk_type != b"IHDR" {
+                    self.write_chunk(chunk_type, data)?;
+                }
+            }
+        }
+
+        // Full size frame at the origin, replacing the previous one. Delay in milliseconds
+        let mut control = Vec::with_capacity(26);
+        control.extend_from_slice(&self.sequence.to_be_bytes());
+        control.extend_from_slice(&header[0..8]);
+        control.extend_from_slice(&[0; 8]);
+        control.extend_from_slice(&((delay * 1000.0).round().min(u16::MAX as f32) as u16).to_be_bytes());
+        control.extend_from_slice(&1000u16.to_be_bytes());
+        control.extend_from_slice(&[0, 0]);
+
+        self.write_chunk(b"fcTL", &control)?;
+        self.sequence += 1;
+
+        for (_, data) in chunks.iter().filter(|(chunk_type, _)| chunk_type == b"IDAT") {
+            match self.frames {
+                0 => self.write_chunk(b"IDAT", data)?,
+
+                // Later frames carry the same data as fdAT, prefixed by the sequence number
+                _ => {
+                    let frame_data = [&self.sequence.to_be_bytes()[..], data].concat();
+                    self.write_chunk(b"fdAT", &frame_data)?;
+                    self.sequence += 1;
+                }
+            }
+        }
+
+        self.frames += 1;
+
+        Ok(())
+    }
+}
+
+// (type, data) of every chunk in a png file
+fn png_chunks(png: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+    let mut chunks = Vec::new();
+    let mut rest = png.strip_prefix(&SIGNATURE[..]).ok_or("Not a png file")?;
+
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes(rest[0..4].try_into().unwrap()) as usize;
+        let chunk_type: [u8; 4] = rest[4..8].try_into().unwrap();
+
+        if rest.len() < 12 + length {
+            return Err("Truncated png chunk".to_string());
+        }
+
+        chunks.push((chunk_type, &rest[8..8 + length]));
+        rest = &rest[12 + length..];
+    }
+
+    Ok(chunks)
+}
+
+impl FrameWriter for ApngWriter {
+    fn write_frame(&mut self, frame: &Frame, time: f32) -> Result<(), String> {
+        let mut png = Vec::new();
+        encode_png(frame, false, &[], &mut png)?;
+
+        if let Some((_, pending_time)) = self.pending {
+            let delay = time - pending_time;
+
+            self.write_pending(delay)?;
+            self.last_delay = delay;
+        }
+
+        self.pending = Some((png, time));
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        // Nothing was written yet, not even the signature
+        if self.frames == 0 && self.pending.is_none() {
+            let path = self.path.clone();
+            drop(self);
+
+            let _ = std::fs::remove_file(&path);
+            return Err("no frames were recorded, nothing saved".to_string());
+        }
+
+        let delay = self.last_delay;
+        self.write_pending(delay)?;
+        self.write_chunk(b"IEND", &[])?;
+
+        let actl_offset = self.actl_offset;
+        let animation_control = self.animation_control();
+
+        self.file.seek(SeekFrom::Start(actl_offset)).map_err(|e| format!("Failed to finish apng: {}", e))?;
+        self.write_chunk(b"acTL", &animation_control)?;
+
+        self.file.flush().map_err(|e| format!("Failed to finish apng: {}", e))
+    }
+}
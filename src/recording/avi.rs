@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::{ BufWriter, Seek, SeekFrom, Write };
+use std::path::{ Path, PathBuf };
+
+use crate::encoder::{ ChromaSubsampling, FrameEncoder, ShareFormat };
+use crate::frame::Frame;
+use crate::recording::FrameWriter;
+
+const FPS: u32 = 30;
+const QUALITY: u8 = 90;
+const HEADER_SIZE: usize = 224; // everything up to the first chunk in the movi list
+const MAX_MOVI_SIZE: u32 = 1 << 30; // AVI 1.0 players expect files below 1 GiB
+const AVIF_HASINDEX: u32 = 0x10;
+const AVIIF_KEYFRAME: u32 = 0x10;
+
+// Motion JPEG in an AVI 1.0 container at a constant frame rate
+pub struct MjpegAviWriter {
+    file: BufWriter<File>,
+    path: PathBuf,
+    encoder: Box<dyn FrameEncoder>,
+    width: usize,
+    height: usize,
+    index: Vec<(u32, u32, u32)>, // flags, offset from the movi fourcc, size
+    movi_size: u32, // bytes after the movi fourcc
+    max_chunk_size: u32,
+    next_tick: u64
+}
+
+impl MjpegAviWriter {
+    pub fn create(path: &Path) -> Result<MjpegAviWriter, String> {
+        let mut file = BufWriter::new(File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?);
+
+        // Placeholder, rewritten with the real sizes and frame count in finish
+        file.write_all(&[0; HEADER_SIZE]).map_err(|e| format!("Failed to write avi header: {}", e))?;
+
+        Ok(MjpegAviWriter {
+            file,
+            path: path.to_path_buf(),
+            encoder: ShareFormat::Jpeg { quality: QUALITY, subsampling: ChromaSubsampling::Yuv420 }.create_encoder(),
+            width: 0,
+            height: 0,
+            index: Vec::new(),
+            movi_size: 0,
+            max_chunk_size: 0,
+            next_tick: 0
+        })
+    }
+
+    fn write_chunk(&mut self, data: &[u8], flags: u32) -> Result<(), String> {
+        let padding = data.len() % 2; // chunks are word aligned
+
+        if self.movi_size as usize + 8 + data.len() + padding > MAX_MOVI_SIZE as usize {
+            return Err("reached the 1 GiB avi size limit".to_string());
+        }
+
+        self.index.push((flags, self.movi_size + 4, data.len() as u32));
+
+        self.file.write_all(b"00dc").and_then(|_| self.file.write_all(&(data.len() as u32).to_le_bytes()))
+            .and_then(|_| self.file.write_all(data))
+            .and_then(|_| self.file.write_all(&[0; 1][..padding]))
+            .map_err(|e| format!("Failed to write avi frame: {}", e))?;
+
+        self.movi_size += (8 + data.len() + padding) as u32;
+        self.max_chunk_size = self.max_chunk_size.max(data.len() as u32);
+
+        Ok(())
+    }
+
+    fn header(&self, riff_size: u32) -> Vec<u8> {
+        let frames = self.index.len() as u32;
+        let (w, h) = (self.width as u32, self.height as u32);
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+
+        let mut put = |values: &[u32]| values.iter().for_each(|v| header.extend_from_slice(&v.to_le_bytes()));
+        let fourcc = |code: &[u8; 4]| u32::from_le_bytes(*code);
+
+        put(&[fourcc(b"RIFF"), riff_size, fourcc(b"AVI ")]);
+        put(&[fourcc(b"LIST"), 192, fourcc(b"hdrl")]);
+
+        // Main header
+        put(&[fourcc(b"avih"), 56, 1_000_000 / FPS, self.max_chunk_size * FPS, 0, AVIF_HASINDEX, frames, 0, 1, self.max_chunk_size, w, h, 0, 0, 0, 0]);
+        put(&[fourcc(b"LIST"), 116, fourcc(b"strl")]);
+
+        // Stream header, priority and language are u16 as are the four values of the frame rectangle
+        put(&[fourcc(b"strh"), 56, fourcc(b"vids"), fourcc(b"MJPG"), 0, 0, 0, 1, FPS, 0, frames, self.max_chunk_size, u32::MAX, 0, 0, w | (h << 16)]);
+
+        // Bitmap info header
+        put(&[fourcc(b"strf"), 40, 40, w, h, 1 | (24 << 16), fourcc(b"MJPG"), w * h * 3, 0, 0, 0, 0]);
+
+        put(&[fourcc(b"LIST"), self.movi_size + 4, fourcc(b"movi")]);
+
+        header
+    }
+}
+
+impl FrameWriter for MjpegAviWriter {
+    fn write_frame(&mut self, frame: &Frame, time: f32) -> Result<(), String> {
+        self.width = frame.width;
+        self.height = frame.height;
+
+        // Frames are placed on the constant rate timeline by their timestamp. Faster frames are skipped,
+        // gaps are filled with empty chunks which players treat as a repeat of the previous frame
+        let tick = (time * FPS as f32).round() as u64;
+
+        if tick < self.next_tick {
+            return Ok(());
+        }
+
+        for _ in self.next_tick..tick {
+            self.write_chunk(&[], 0)?;
+        }
+
+        let data = self.encoder.encode(frame)?;
+        self.write_chunk(&data, AVIIF_KEYFRAME)?;
+        self.next_tick = tick + 1;
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        // Only the placeholder header, a 0x0 video without frames
+        if self.index.is_empty() {
+            let path = self.path.clone();
+            drop(self);
+
+            let _ = std::fs::remove_file(&path);
+            return Err("no frames were recorded, nothing saved".to_string());
+        }
+
+        let mut idx1 = Vec::with_capacity(8 + self.index.len() * 16);
+        idx1.extend_from_slice(b"idx1");
+        idx1.extend_from_slice(&(self.index.len() as u32 * 16).to_le_bytes());
+
+        for &(flags, offset, size) in &self.index {
+            idx1.extend_from_slice(b"00dc");
+            [flags, offset, size].iter().for_each(|v| idx1.extend_from_slice(&v.to_le_bytes()));
+        }
+
+        let riff_size = (HEADER_SIZE - 8) as u32 + self.movi_size + idx1.len() as u32;
+        let header = self.header(riff_size);
+
+        self.file.write_all(&idx1)
+            .and_then(|_| self.file.seek(SeekFrom::Start(0)))
+            .and_then(|_| self.file.write_all(&header))
+            .and_then(|_| self.file.flush())
+            .map_err(|e| format!("Failed to finish avi: {}", e))
+    }
+}
@@ -0,0 +1,76 @@
+use image::codecs::gif::{ GifEncoder, Repeat };
+use image::{ Delay, RgbaImage };
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{ Path, PathBuf };
+
+use crate::frame::Frame;
+use crate::recording::FrameWriter;
+
+const SPEED: i32 = 10; // palette quantization, 1 = best, 30 = fastest
+const MIN_DELAY: f32 = 0.02; // browsers slow down shorter gif delays to 100 ms
+
+// Looping animated GIF, the delay of a frame is only known once the next one arrives
+pub struct GifWriter {
+    encoder: GifEncoder<BufWriter<File>>,
+    path: PathBuf,
+    pending: Option<(RgbaImage, f32)>,
+    last_delay: f32
+}
+
+impl GifWriter {
+    pub fn create(path: &Path) -> Result<GifWriter, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+
+        let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), SPEED);
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| format!("Failed to write gif header: {}", e))?;
+
+        Ok(GifWriter { encoder, path: path.to_path_buf(), pending: None, last_delay: 0.1 })
+    }
+
+    fn encode_pending(&mut self, delay: f32) -> Result<(), String> {
+        if let Some((image, _)) = self.pending.take() {
+            let delay = Delay::from_numer_denom_ms((delay * 1000.0).round() as u32, 1);
+            self.encoder.encode_frame(image::Frame::from_parts(image, 0, 0, delay)).map_err(|e| format!("Failed to encode gif frame: {}", e))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FrameWriter for GifWriter {
+    fn write_frame(&mut self, frame: &Frame, time: f32) -> Result<(), String> {
+        if let Some((_, pending_time)) = self.pending {
+            let delay = time - pending_time;
+
+            // Too soon after the previous frame, it is shown for longer instead
+            if delay < MIN_DELAY {
+                return Ok(());
+            }
+
+            self.encode_pending(delay)?;
+            self.last_delay = delay;
+        }
+
+        let rgba = frame.with_channels(4).into_owned();
+        let image = RgbaImage::from_raw(rgba.width as u32, rgba.height as u32, rgba.pixels).ok_or("Frame does not match its size")?;
+        self.pending = Some((image, time));
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        // Only the header was written, the first frame is always pending
+        if self.pending.is_none() {
+            let path = self.path.clone();
+            drop(self);
+
+            let _ = std::fs::remove_file(&path);
+            return Err("no frames were recorded, nothing saved".to_string());
+        }
+
+        // The trailer is written when the encoder is dropped
+        let delay = self.last_delay;
+        self.encode_pending(delay)
+    }
+}
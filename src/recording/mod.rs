@@ -0,0 +1,128 @@
+pub mod avi;
+pub mod gif;
+pub mod apng;
+pub mod png_sequence;
+
+use std::path::{ Path, PathBuf };
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{ Instant, SystemTime, UNIX_EPOCH };
+
+use crate::frame::Frame;
+use crate::scaling::{ ScaleFilter, scale_frame };
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordFormat {
+    MjpegAvi,
+    Gif,
+    Apng,
+    PngSequence
+}
+
+impl RecordFormat {
+    pub fn next(self) -> Self {
+        match self {
+            RecordFormat::MjpegAvi => RecordFormat::Gif,
+            RecordFormat::Gif => RecordFormat::Apng,
+            RecordFormat::Apng => RecordFormat::PngSequence,
+            RecordFormat::PngSequence => RecordFormat::MjpegAvi
+        }
+    }
+
+    // PNG sequences are written into a directory
+    fn extension(self) -> &'static str {
+        match self {
+            RecordFormat::MjpegAvi => ".avi",
+            RecordFormat::Gif => ".gif",
+            RecordFormat::Apng => ".png",
+            RecordFormat::PngSequence => ""
+        }
+    }
+
+    fn create_writer(self, path: &Path) -> Result<Box<dyn FrameWriter>, String> {
+        Ok(match self {
+            RecordFormat::MjpegAvi => Box::new(avi::MjpegAviWriter::create(path)?),
+            RecordFormat::Gif => Box::new(gif::GifWriter::create(path)?),
+            RecordFormat::Apng => Box::new(apng::ApngWriter::create(path)?),
+            RecordFormat::PngSequence => Box::new(png_sequence::PngSequenceWriter::create(path)?)
+        })
+    }
+}
+
+pub trait FrameWriter: Send {
+    // time = seconds since the first recorded frame, every frame has the size of the first one
+    fn write_frame(&mut self, frame: &Frame, time: f32) -> Result<(), String>;
+
+    // Completes the file, headers that depend on the frame count are written here
+    fn finish(self: Box<Self>) -> Result<(), String>;
+}
+
+// Writes frames on its own thread until stopped, the file is only complete after stop
+pub struct Recorder {
+    frame_tx: mpsc::SyncSender<(Frame, Instant)>,
+    thread: JoinHandle<()>,
+    dropped: u32
+}
+
+impl Recorder {
+    pub fn start(format: RecordFormat) -> Result<Recorder, String> {
+        std::fs::create_dir_all("recordings").map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+        // Milliseconds, so a recording restarted within the same second does not replace the last one
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let path = PathBuf::from(format!("recordings/recording_{}_{:03}{}", now.as_secs(), now.subsec_millis(), format.extension()));
+
+        let mut writer = format.create_writer(&path)?;
+        let (frame_tx, frame_rx) = mpsc::sync_channel::<(Frame, Instant)>(4);
+
+        println!("Recording {:?} to {}", format, path.display());
+
+        let thread = std::thread::spawn(move || {
+            let mut first: Option<(Instant, usize, usize)> = None;
+            let mut frames = 0;
+
+            while let Ok((frame, requested)) = frame_rx.recv() {
+                let (start, width, height) = *first.get_or_insert((requested, frame.width, frame.height));
+                let time = requested.duration_since(start).as_secs_f32();
+
+                // The window can be resized while recording, frames are scaled to the size of the first one
+                let result = match frame.width == width && frame.height == height {
+                    true => writer.write_frame(&frame, time),
+                    false => writer.write_frame(&scale_frame(&frame, width, height, ScaleFilter::Area), time)
+                };
+
+                if let Err(e) = result {
+                    println!("Recording stopped: {}", e);
+                    break;
+                }
+
+                frames += 1;
+            }
+
+            match writer.finish() {
+                Ok(()) => println!("Recording saved to {} ({} frames)", path.display(), frames),
+                Err(e) => println!("Failed to finish recording {}: {}", path.display(), e)
+            }
+        });
+
+        Ok(Recorder { frame_tx, thread, dropped: 0 })
+    }
+
+    // requested = when the frame was rendered, try_send - if the writer is still busy, drop the frame
+    pub fn push(&mut self, frame: Frame, requested: Instant) {
+        if let Err(mpsc::TrySendError::Full(_)) = self.frame_tx.try_send((frame, requested)) {
+            self.dropped += 1;
+        }
+    }
+
+    // Waits until the writer thread finished the file
+    pub fn stop(self) {
+        drop(self.frame_tx);
+
+        if self.dropped > 0 {
+            println!("Recording dropped {} frames, the writer could not keep up", self.dropped);
+        }
+
+        let _ = self.thread.join();
+    }
+}
@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::io::{ BufWriter, Write };
+use std::path::{ Path, PathBuf };
+
+use crate::encoder::encode_png;
+use crate::frame::Frame;
+use crate::recording::FrameWriter;
+
+// Numbered PNG files in a directory, timestamps.txt lists each file with its time in seconds
+pub struct PngSequenceWriter {
+    directory: PathBuf,
+    timestamps: BufWriter<File>,
+    frames: usize
+}
+
+impl PngSequenceWriter {
+    pub fn create(directory: &Path) -> Result<PngSequenceWriter, String> {
+        std::fs::create_dir_all(directory).map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+
+        let timestamps = File::create(directory.join("timestamps.txt")).map_err(|e| format!("Failed to create timestamps file: {}", e))?;
+
+        Ok(PngSequenceWriter { directory: directory.to_path_buf(), timestamps: BufWriter::new(timestamps), frames: 0 })
+    }
+}
+
+impl FrameWriter for PngSequenceWriter {
+    fn write_frame(&mut self, frame: &Frame, time: f32) -> Result<(), String> {
+        let name = format!("frame_{:05}.png", self.frames);
+        let file = File::create(self.directory.join(&name)).map_err(|e| format!("Failed to create {}: {}", name, e))?;
+
+        encode_png(frame, false, &[], BufWriter::new(file))?;
+        writeln!(self.timestamps, "{} {:.6}", name, time).map_err(|e| format!("Failed to write timestamp: {}", e))?;
+
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        // Only the empty timestamps file is in the directory
+        if self.frames == 0 {
+            let directory = self.directory.clone();
+            drop(self);
+
+            let _ = std::fs::remove_file(directory.join("timestamps.txt")).and_then(|_| std::fs::remove_dir(&directory));
+            return Err("no frames were recorded, nothing saved".to_string());
+        }
+
+        self.timestamps.flush().map_err(|e| format!("Failed to write timestamps: {}", e))
+    }
+}
@@ -0,0 +1,381 @@
+use crate::frame::Frame;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScaleFilter {
+    Nearest,
+    Box,
+    Bilinear,
+    Lanczos3,
+    Area
+}
+
+impl ScaleFilter {
+    pub fn next(self) -> Self {
+        match self {
+            ScaleFilter::Nearest => ScaleFilter::Box,
+            ScaleFilter::Box => ScaleFilter::Bilinear,
+            ScaleFilter::Bilinear => ScaleFilter::Lanczos3,
+            ScaleFilter::Lanczos3 => ScaleFilter::Area,
+            ScaleFilter::Area => ScaleFilter::Nearest
+        }
+    }
+
+    fn support(self) -> f32 {
+        match self {
+            ScaleFilter::Box => 0.5,
+            ScaleFilter::Bilinear => 1.0,
+            ScaleFilter::Lanczos3 => 3.0,
+            ScaleFilter::Nearest | ScaleFilter::Area => 0.0
+        }
+    }
+
+    fn kernel(self, x: f32) -> f32 {
+        match self {
+            ScaleFilter::Box => if (-0.5..0.5).contains(&x) { 1.0 } else { 0.0 },
+            ScaleFilter::Bilinear => (1.0 - x.abs()).max(0.0),
+            ScaleFilter::Lanczos3 => if x.abs() < 3.0 { sinc(x) * sinc(x / 3.0) } else { 0.0 },
+            ScaleFilter::Nearest | ScaleFilter::Area => 0.0
+        }
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x == 0.0 {
+        return 1.0;
+    }
+
+    let x = x * std::f32::consts::PI;
+    x.sin() / x
+}
+
+// Source pixels and normalized weights that make up one destination pixel
+struct Contribution {
+    start: usize,
+    weights: Vec<f32>
+}
+
+fn contributions(src_len: usize, dst_len: usize, filter: ScaleFilter) -> Vec<Contribution> {
+    let scale = src_len as f32 / dst_len as f32;
+
+    (0..dst_len).map(|i| {
+        match filter {
+            ScaleFilter::Nearest => {
+                let start = (((i as f32 + 0.5) * scale) as usize).min(src_len - 1);
+
+                Contribution { start, weights: vec![1.0] }
+            },
+
+            // Every source pixel is weighted by how much of it the destination pixel covers
+            ScaleFilter::Area => {
+                let (x0, x1) = (i as f32 * scale, (i + 1) as f32 * scale);
+                let start = (x0.floor() as usize).min(src_len - 1);
+                let end = (x1.ceil() as usize).clamp(start + 1, src_len);
+
+                let weights: Vec<f32> = (start..end).map(|j| (x1.min((j + 1) as f32) - x0.max(j as f32)).max(0.0)).collect();
+                let sum: f32 = weights.iter().sum();
+
+                Contribution { start, weights: weights.iter().map(|w| w / sum).collect() }
+            },
+
+            // Kernel is stretched when shrinking, so it always covers every source pixel in the footprint
+            _ => {
+                let filter_scale = scale.max(1.0);
+                let support = filter.support() * filter_scale;
+                let center = (i as f32 + 0.5) * scale;
+
+                let start = ((center - support).floor().max(0.0) as usize).min(src_len - 1);
+                let end = ((center + support).ceil() as usize).clamp(start + 1, src_len);
+
+                let weights: Vec<f32> = (start..end).map(|j| filter.kernel((j as f32 + 0.5 - center) / filter_scale)).collect();
+                let sum: f32 = weights.iter().sum();
+
+                match sum.abs() > f32::EPSILON {
+                    true => Contribution { start, weights: weights.iter().map(|w| w / sum).collect() },
+                    false => Contribution { start: (center as usize).min(src_len - 1), weights: vec![1.0] }
+                }
+            }
+        }
+    }).collect()
+}
+
+// Separable resample to any size, horizontal pass first into a float buffer, then vertical
+pub fn resize(src: &Frame, width: usize, height: usize, filter: ScaleFilter) -> Frame {
+    let c = src.channels;
+    let (width, height) = (width.max(1), height.max(1));
+
+    let columns = contributions(src.width, width, filter);
+    let rows = contributions(src.height, height, filter);
+
+    let mut horizontal = vec![0.0f32; width * src.height * c];
+
+    for y in 0..src.height {
+        let src_row = &src.pixels[y * src.width * c..(y + 1) * src.width * c];
+        let dst_row = &mut horizontal[y * width * c..(y + 1) * width * c];
+
+        for (x, contribution) in columns.iter().enumerate() {
+            for (k, w) in contribution.weights.iter().enumerate() {
+                let i = (contribution.start + k) * c;
+
+                for ch in 0..c {
+                    dst_row[x * c + ch] += src_row[i + ch] as f32 * w;
+                }
+            }
+        }
+    }
+
+    let mut pixels = vec![0u8; width * height * c];
+    let mut acc = vec![0.0f32; width * c];
+
+    for (y, contribution) in rows.iter().enumerate() {
+        acc.fill(0.0);
+
+        for (k, w) in contribution.weights.iter().enumerate() {
+            let src_row = &horizontal[(contribution.start + k) * width * c..(contribution.start + k + 1) * width * c];
+
+            for (a, s) in acc.iter_mut().zip(src_row) {
+                *a += s * w;
+            }
+        }
+
+        for (dst, a) in pixels[y * width * c..(y + 1) * width * c].iter_mut().zip(&acc) {
+            *dst = a.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    Frame::new(pixels, width, height, c)
+}
+
+pub fn scale_frame(src: &Frame, width: usize, height: usize, filter: ScaleFilter) -> Frame {
+    if width == src.width && height == src.height {
+        return src.clone();
+    }
+
+    // Box and area average are the same filter at exactly half size, which has a fast path
+    if width == src.width / 2 && height == src.height / 2 && matches!(filter, ScaleFilter::Box | ScaleFilter::Area) {
+        return box_downscale_2x(src);
+    }
+
+    resize(src, width, height, filter)
+}
+
+// Average of every 2x2 block, rounded
+pub fn box_downscale_2x(src: &Frame) -> Frame {
+    let c = src.channels;
+    let (w, new_w, new_h) = (src.width, src.width / 2, src.height / 2);
+    let mut pixels = vec![0u8; new_w * new_h * c];
+
+    for y in 0..new_h {
+        let row0 = &src.pixels[(2 * y) * w * c..(2 * y + 1) * w * c];
+        let row1 = &src.pixels[(2 * y + 1) * w * c..(2 * y + 2) * w * c];
+        let dst = &mut pixels[y * new_w * c..(y + 1) * new_w * c];
+
+        #[cfg(target_arch = "x86_64")]
+        let done = if c == 4 { box_2x_rgba_row_sse2(row0, row1, dst) } else { 0 };
+        #[cfg(not(target_arch = "x86_64"))]
+        let done = 0;
+
+        for x in done..new_w {
+            for ch in 0..c {
+                let (i, j) = (2 * x * c + ch, (2 * x + 1) * c + ch);
+                let sum = row0[i] as u16 + row0[j] as u16 + row1[i] as u16 + row1[j] as u16;
+
+                dst[x * c + ch] = ((sum + 2) >> 2) as u8;
+            }
+        }
+    }
+
+    Frame::new(pixels, new_w, new_h, c)
+}
+
+// SSE2 is part of every x86_64 cpu. Handles two destination pixels per step, returns how many were written
+#[cfg(target_arch = "x86_64")]
+fn box_2x_rgba_row_sse2(row0: &[u8], row1: &[u8], dst: &mut [u8]) -> usize {
+    use std::arch::x86_64::*;
+
+    let new_w = dst.len() / 4;
+    let mut x = 0;
+
+    unsafe {
+        let zero = _mm_setzero_si128();
+        let two = _mm_set1_epi16(2);
+
+        while x + 2 <= new_w {
+            // 4 source pixels from each row
+            let a = _mm_loadu_si128(row0.as_ptr().add(x * 8) as *const __m128i);
+            let b = _mm_loadu_si128(row1.as_ptr().add(x * 8) as *const __m128i);
+
+            // Widen to u16 and add the rows, lo = pixels 0 and 1, hi = pixels 2 and 3
+            let lo = _mm_add_epi16(_mm_unpacklo_epi8(a, zero), _mm_unpacklo_epi8(b, zero));
+            let hi = _mm_add_epi16(_mm_unpackhi_epi8(a, zero), _mm_unpackhi_epi8(b, zero));
+
+            // Add horizontal neighbours, each sum ends up in the low 64 bits
+            let sum_lo = _mm_add_epi16(lo, _mm_srli_si128(lo, 8));
+            let sum_hi = _mm_add_epi16(hi, _mm_srli_si128(hi, 8));
+            let sum = _mm_unpacklo_epi64(sum_lo, sum_hi);
+
+            let avg = _mm_srli_epi16(_mm_add_epi16(sum, two), 2);
+            _mm_storel_epi64(dst.as_mut_ptr().add(x * 4) as *mut __m128i, _mm_packus_epi16(avg, avg));
+
+            x += 2;
+        }
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(width: usize, height: usize, pixels: &[u8]) -> Frame {
+        Frame::new(pixels.to_vec(), width, height, 1)
+    }
+
+    fn resized(src: &Frame, width: usize, height: usize, filter: ScaleFilter) -> Vec<u8> {
+        let frame = resize(src, width, height, filter);
+        assert_eq!((frame.width, frame.height, frame.channels), (width, height, src.channels));
+
+        frame.pixels
+    }
+
+    // Scalar version of the 2x2 average, the reference for the SSE2 path
+    fn box_2x_scalar(src: &Frame) -> Vec<u8> {
+        let c = src.channels;
+        let (new_w, new_h) = (src.width / 2, src.height / 2);
+        let at = |x: usize, y: usize, ch: usize| src.pixels[(y * src.width + x) * c + ch] as u16;
+
+        (0..new_h).flat_map(|y| (0..new_w).flat_map(move |x| (0..c).map(move |ch| {
+            ((at(2 * x, 2 * y, ch) + at(2 * x + 1, 2 * y, ch) + at(2 * x, 2 * y + 1, ch) + at(2 * x + 1, 2 * y + 1, ch) + 2) >> 2) as u8
+        }))).collect()
+    }
+
+    // Deterministic noise so every byte lane is exercised
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+
+        (0..len).map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 24) as u8
+        }).collect()
+    }
+
+    #[test]
+    fn nearest() {
+        assert_eq!(resized(&gray(4, 1, &[10, 20, 30, 40]), 2, 1, ScaleFilter::Nearest), [20, 40]);
+
+        // 5 -> 3 samples at 0.83, 2.5 and 4.17
+        assert_eq!(resized(&gray(5, 1, &[10, 20, 30, 40, 50]), 3, 1, ScaleFilter::Nearest), [10, 30, 50]);
+
+        // Upscaling repeats pixels, 2 -> 3 rows samples at 0.33, 1.0 and 1.67
+        assert_eq!(resized(&gray(2, 2, &[1, 2, 3, 4]), 4, 3, ScaleFilter::Nearest), [1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4]);
+    }
+
+    #[test]
+    fn box_2x() {
+        let src = Frame::new(vec![
+            0, 10, 20, 255,   4, 14, 24, 255,   100, 0, 0, 0,   101, 1, 1, 1,
+            2, 12, 22, 255,   6, 16, 26, 255,   102, 2, 2, 2,   103, 3, 3, 3
+        ], 4, 2, 4);
+
+        // (0 + 4 + 2 + 6 + 2) / 4 = 3, (100 + 101 + 102 + 103 + 2) / 4 = 102 (rounded)
+        let expected = [3, 13, 23, 255, 102, 2, 2, 2];
+
+        assert_eq!(box_downscale_2x(&src).pixels, expected);
+        assert_eq!(scale_frame(&src, 2, 1, ScaleFilter::Box).pixels, expected);
+        assert_eq!(scale_frame(&src, 2, 1, ScaleFilter::Area).pixels, expected);
+
+        // Odd width and height, the last column and row are dropped
+        let odd = gray(5, 3, &[
+            10, 20, 30, 40, 99,
+            30, 40, 50, 60, 99,
+            99, 99, 99, 99, 99
+        ]);
+
+        assert_eq!(box_downscale_2x(&odd).pixels, [25, 45]);
+    }
+
+    #[test]
+    fn box_non_integer() {
+        // 3 -> 2: centers at 0.75 and 2.25 with a kernel 1.5 pixels wide, the right edge is exclusive
+        assert_eq!(resized(&gray(3, 1, &[0, 90, 180]), 2, 1, ScaleFilter::Box), [0, 135]);
+    }
+
+    #[test]
+    fn bilinear() {
+        // 4 -> 2: weights 0.75, 0.75, 0.25 normalized, (0 * 0.75 + 100 * 0.75 + 200 * 0.25) / 1.75 = 71.4
+        assert_eq!(resized(&gray(4, 1, &[0, 100, 200, 255]), 2, 1, ScaleFilter::Bilinear), [71, 209]);
+
+        // 2 -> 4: samples at 0.25, 0.75, 1.25 and 1.75, clamped at the borders
+        assert_eq!(resized(&gray(2, 1, &[0, 100]), 4, 1, ScaleFilter::Bilinear), [0, 25, 75, 100]);
+
+        // 3 -> 2: (90 * 0.5) / 1.333 = 33.75 and (90 * 0.5 + 180 * 0.833) / 1.333 = 146.25
+        assert_eq!(resized(&gray(3, 1, &[0, 90, 180]), 2, 1, ScaleFilter::Bilinear), [34, 146]);
+
+        // Same weights vertically
+        assert_eq!(resized(&gray(1, 3, &[0, 90, 180]), 1, 2, ScaleFilter::Bilinear), [34, 146]);
+    }
+
+    #[test]
+    fn lanczos3() {
+        // Ringing around the step is clamped: -16.8, 103.4, 217.1
+        assert_eq!(resized(&gray(8, 1, &[0, 0, 0, 10, 200, 200, 200, 200]), 3, 1, ScaleFilter::Lanczos3), [0, 103, 217]);
+
+        // 2 -> 5 overshoots past both ends: 15.7, 50.5, 100, 149.5, 184.3
+        assert_eq!(resized(&gray(2, 1, &[40, 160]), 5, 1, ScaleFilter::Lanczos3), [16, 50, 100, 150, 184]);
+
+        // Odd width, 5 -> 3: 93.1, 112.5, 45.5
+        assert_eq!(resized(&gray(5, 1, &[10, 200, 60, 90, 30]), 3, 1, ScaleFilter::Lanczos3), [93, 113, 45]);
+
+        // A flat frame stays flat
+        assert_eq!(resized(&gray(7, 5, &[77; 35]), 3, 4, ScaleFilter::Lanczos3), [77; 12]);
+    }
+
+    #[test]
+    fn area() {
+        // 3 -> 2: (0 + 90 * 0.5) / 1.5 = 30 and (90 * 0.5 + 180) / 1.5 = 150
+        assert_eq!(resized(&gray(3, 1, &[0, 90, 180]), 2, 1, ScaleFilter::Area), [30, 150]);
+
+        // Odd width, 5 -> 2: (10 + 20 + 15) / 2.5 = 18 and (15 + 40 + 50) / 2.5 = 42
+        assert_eq!(resized(&gray(5, 1, &[10, 20, 30, 40, 50]), 2, 1, ScaleFilter::Area), [18, 42]);
+
+        // Both directions, 3x3 -> 2x2
+        let src = gray(3, 3, &[
+            0, 90, 180,
+            0, 90, 180,
+            60, 60, 60
+        ]);
+
+        // Rows become [30, 150], [30, 150], [60, 60], then (30 + 15) / 1.5 = 30, (15 + 60) / 1.5 = 50 ...
+        assert_eq!(resized(&src, 2, 2, ScaleFilter::Area), [30, 150, 50, 90]);
+    }
+
+    #[test]
+    fn area_rgba_keeps_channels_apart() {
+        let src = Frame::new(vec![255, 0, 0, 255,   0, 0, 255, 255,   0, 255, 0, 0], 3, 1, 4);
+
+        // 2 / 3 of the first pixel and 1 / 3 of the second, then 1 / 3 of the second and 2 / 3 of the third
+        assert_eq!(resized(&src, 2, 1, ScaleFilter::Area), [170, 0, 85, 255, 0, 170, 85, 85]);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn sse2_matches_scalar() {
+        // Source widths 2..=21 give 1..=10 destination pixels, odd counts leave a tail after the 2 pixel steps
+        for width in 2..=21 {
+            for height in [2, 3, 4] {
+                let src = Frame::new(noise(width * height * 4, (width * 31 + height) as u32), width, height, 4);
+                let expected = box_2x_scalar(&src);
+
+                assert_eq!(box_downscale_2x(&src).pixels, expected, "{}x{}", width, height);
+
+                // The SSE2 row alone, before the scalar loop finishes the tail
+                let new_w = width / 2;
+                let mut dst = vec![0u8; new_w * 4];
+                let done = box_2x_rgba_row_sse2(&src.pixels[..width * 4], &src.pixels[width * 4..width * 8], &mut dst);
+
+                assert_eq!(done, new_w / 2 * 2, "{}x{}", width, height);
+                assert_eq!(&dst[..done * 4], &expected[..done * 4], "{}x{}", width, height);
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use crate::encoder::encode_png;
+use crate::frame::Frame;
+use crate::scaling::{ ScaleFilter, scale_frame };
+
+// Longest parameter part of a file name, file names are limited to 255 bytes
+const MAX_NAME_PARAMETERS: usize = 160;
+
+// Saves the effect output and the unprocessed source, optionally side by side, on a separate thread.
+// The runtime parameters of the effect go into the file name, every uniform into the PNG text
+pub fn save_screenshot(output: Frame, source: Frame, effect: String, parameters: Vec<(&'static str, f32)>, uniforms: BTreeMap<String, String>, composite: bool) {
+    std::thread::spawn(move || {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = now.as_secs();
+
+        // e.g. screenshots/ascii_cell_average=0_edge_detector=1_1792391968_042_output.png, milliseconds so screenshots within a second don't replace each other
+        let mut parameters: String = parameters.iter().map(|(name, value)| format!("_{}={}", name, value)).collect();
+
+        if parameters.len() > MAX_NAME_PARAMETERS {
+            let end = (0..=MAX_NAME_PARAMETERS).rev().find(|&i| parameters.is_char_boundary(i)).unwrap_or(0);
+            parameters.truncate(end);
+        }
+
+        let base = format!("screenshots/{}{}_{}_{:03}", effect.to_lowercase(), parameters, seconds, now.subsec_millis());
+
+        // Settings are also embedded in every file
+        let settings = uniforms.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join("; ");
+        let text = |description: &str| vec![
+            ("Software", "Shader_window".to_string()),
+            ("Description", description.to_string()),
+            ("Effect", effect.clone()),
+            ("Parameters", settings.clone()),
+            ("Timestamp", seconds.to_string())
+        ];
+
+        let mut images = vec![
+            (format!("{}_output.png", base), text("Effect output"), output),
+            (format!("{}_source.png", base), text("Source before the effect"), source)
+        ];
+
+        if composite {
+            images.push((format!("{}_composite.png", base), text("Source (left) and effect output (right)"), side_by_side(&images[1].2, &images[0].2)));
+        }
+
+        let result = std::fs::create_dir_all("screenshots").map_err(|e| format!("Failed to create screenshots directory: {}", e)).and_then(|_| {
+            images.iter().try_for_each(|(path, text, frame)| {
+                let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+                encode_png(frame, false, text, BufWriter::new(file))
+            })
+        });
+
+        match result {
+            Ok(()) => println!("Screenshot saved: {}_*.png", base),
+            Err(e) => println!("Failed to save screenshot: {}", e)
+        }
+    });
+}
+
+// Source scaled to the output size on the left, output on the right
+fn side_by_side(source: &Frame, output: &Frame) -> Frame {
+    let source = scale_frame(source, output.width, output.height, ScaleFilter::Bilinear);
+    let row = output.width * 4;
+
+    let pixels = source.pixels.chunks_exact(row).zip(output.pixels.chunks_exact(row))
+        .flat_map(|(left, right)| left.iter().chain(right))
+        .copied()
+        .collect();
+
+    Frame::new(pixels, output.width * 2, output.height, 4)
+}
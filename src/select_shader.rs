@@ -1,10 +1,13 @@
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug)]
 pub enum SelectShader {
     None,
     Test,
     Pixel,
     Ascii,
-    EdgeDetect
+    EdgeDetect,
+    Kuwahara,
+    Halftone,
+    ColorVision
 }
 
 impl SelectShader {
@@ -14,7 +17,10 @@ impl SelectShader {
             SelectShader::Test => SelectShader::Pixel,
             SelectShader::Pixel => SelectShader::Ascii,
             SelectShader::Ascii => SelectShader::EdgeDetect,
-            SelectShader::EdgeDetect => SelectShader::None
+            SelectShader::EdgeDetect => SelectShader::Kuwahara,
+            SelectShader::Kuwahara => SelectShader::Halftone,
+            SelectShader::Halftone => SelectShader::ColorVision,
+            SelectShader::ColorVision => SelectShader::None
         }
     }
 }
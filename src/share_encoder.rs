@@ -0,0 +1,89 @@
+use axum::body::Bytes;
+use std::collections::HashMap;
+use std::sync::{ mpsc, Arc };
+use std::time::Instant;
+
+use crate::encoder::FrameEncoder;
+use crate::frame::{ Frame, Yuv420Frame };
+use crate::scaling::{ ScaleFilter, scale_frame };
+use crate::share_hub::{ ShareHub, ShareStream };
+
+pub enum ShareFrame {
+    // Full size window pixels, true when the streams that take 4:2:0 planes get them from the GPU instead
+    Rgba(Frame, bool),
+    // Planes from the GPU conversion and the share scale they were made at
+    Yuv420(Yuv420Frame, usize)
+}
+
+impl ShareFrame {
+    fn serves(&self, stream: &ShareStream) -> bool {
+        match self {
+            ShareFrame::Rgba(_, gpu_planes) => !(*gpu_planes && stream.format.takes_yuv420()),
+            ShareFrame::Yuv420(..) => stream.format.takes_yuv420()
+        }
+    }
+}
+
+// Encodes every received frame once per stream it serves, pixel buffers are sent back through the recyclers
+pub fn spawn_encoder_thread(hub: Arc<ShareHub>, frame_rx: mpsc::Receiver<(Vec<ShareFrame>, ScaleFilter)>, rgba_recycler: mpsc::Sender<Vec<u8>>, yuv_recycler: mpsc::Sender<Vec<u8>>) {
+    std::thread::spawn(move || {
+        let mut encoders: HashMap<ShareStream, Box<dyn FrameEncoder>> = HashMap::new();
+
+        while let Ok((share_frames, filter)) = frame_rx.recv() {
+            let mut streams = hub.active_streams();
+            streams.sort_by_key(|s| s.scale_percent);
+
+            for share_frame in share_frames {
+                encode_frame(&hub, &mut encoders, &share_frame, &streams, filter);
+
+                // Hand the pixel buffer back to the readback so it is reused for a later frame
+                let _ = match share_frame {
+                    ShareFrame::Rgba(frame, _) => rgba_recycler.send(frame.pixels),
+                    ShareFrame::Yuv420(planes, _) => yuv_recycler.send(planes.pixels)
+                };
+            }
+
+            // Forget encoders of streams nobody watches anymore
+            encoders.retain(|stream, _| streams.contains(stream));
+        }
+    });
+}
+
+fn encode_frame(hub: &ShareHub, encoders: &mut HashMap<ShareStream, Box<dyn FrameEncoder>>, share_frame: &ShareFrame, streams: &[ShareStream], filter: ScaleFilter) {
+    // Streams are grouped by scale, so each resolution is produced only once
+    let mut scaled: Option<(usize, Frame)> = None;
+    let mut converted: Option<Frame> = None; // RGB of the GPU planes, only made when a stream needs it
+
+    for &stream in streams.iter().filter(|s| share_frame.serves(s)) {
+        let start = Instant::now();
+        let encoder = encoders.entry(stream).or_insert_with(|| stream.format.create_encoder());
+
+        let encoded = match share_frame {
+            ShareFrame::Yuv420(planes, percent) if *percent == stream.scale_percent => encoder.encode_yuv420(planes),
+
+            _ => {
+                let (base, base_percent) = match share_frame {
+                    ShareFrame::Rgba(frame, _) => (frame, 100),
+                    ShareFrame::Yuv420(planes, percent) => (&*converted.get_or_insert_with(|| planes.to_rgb()), *percent)
+                };
+
+                if scaled.as_ref().is_none_or(|(percent, _)| *percent != stream.scale_percent) {
+                    // Reduce resolution before encoding
+                    let w = base.width * stream.scale_percent / base_percent;
+                    let h = base.height * stream.scale_percent / base_percent;
+
+                    scaled = Some((stream.scale_percent, scale_frame(base, w, h, filter)));
+                }
+
+                encoder.encode(&scaled.as_ref().unwrap().1)
+            }
+        };
+
+        match encoded {
+            Ok(encoded) => hub.publish(stream, Bytes::from(encoded)),
+            Err(e) => println!("Failed to encode {:?}: {}", stream, e)
+        }
+
+        println!("encode {:?}: {:?}", stream, start.elapsed());
+    }
+}
@@ -0,0 +1,72 @@
+use axum::body::Bytes;
+use std::collections::HashMap;
+use std::sync::{ Mutex, RwLock };
+use std::time::{ Duration, Instant };
+use tokio::sync::broadcast;
+
+use crate::adaptive_quality::AdaptiveQuality;
+use crate::encoder::ShareFormat;
+
+const SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(1); // older frames are from before sharing stopped
+
+// Encoded format together with the size the window is scaled to before encoding
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ShareStream {
+    pub format: ShareFormat,
+    pub scale_percent: usize
+}
+
+// One broadcast channel per stream in use, so every frame is encoded once per distinct stream
+pub struct ShareHub {
+    channels: Mutex<HashMap<ShareStream, broadcast::Sender<Bytes>>>,
+    last_jpeg: RwLock<Option<(Instant, Bytes)>> // latest frame of the snapshot stream and when it was published
+}
+
+impl ShareHub {
+    pub fn new() -> Self {
+        Self { channels: Mutex::new(HashMap::new()), last_jpeg: RwLock::new(None) }
+    }
+
+    pub fn subscribe(&self, stream: ShareStream) -> broadcast::Receiver<Bytes> {
+        let mut channels = self.channels.lock().unwrap();
+
+        channels.entry(stream).or_insert_with(|| broadcast::channel(4).0).subscribe()
+    }
+
+    // Streams with at least one viewer, channels nobody listens to anymore are dropped
+    pub fn active_streams(&self) -> Vec<ShareStream> {
+        let mut channels = self.channels.lock().unwrap();
+        channels.retain(|_, tx| tx.receiver_count() > 0);
+
+        channels.keys().copied().collect()
+    }
+
+    pub fn has_viewers(&self) -> bool {
+        let channels = self.channels.lock().unwrap();
+
+        channels.values().any(|tx| tx.receiver_count() > 0)
+    }
+
+    // Best quality stream of a default viewer, the one snapshots are taken from
+    pub fn snapshot_stream() -> ShareStream {
+        AdaptiveQuality::new("Snapshot", ShareFormat::default()).stream()
+    }
+
+    pub fn last_jpeg(&self) -> Option<Bytes> {
+        let last = self.last_jpeg.read().unwrap();
+
+        last.as_ref().filter(|(published, _)| published.elapsed() < SNAPSHOT_MAX_AGE).map(|(_, frame)| frame.clone())
+    }
+
+    pub fn publish(&self, stream: ShareStream, frame: Bytes) {
+        if stream == ShareHub::snapshot_stream() {
+            *self.last_jpeg.write().unwrap() = Some((Instant::now(), frame.clone()));
+        }
+
+        let channels = self.channels.lock().unwrap();
+
+        if let Some(tx) = channels.get(&stream) {
+            let _ = tx.send(frame);
+        }
+    }
+}
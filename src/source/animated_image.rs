@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use crate::frame::Frame;
+use crate::source::FrameSource;
+use crate::texture::Texture;
+
+// Browsers show frames with a (near) zero delay for 100 ms, so the same is done here
+const MIN_DELAY: f32 = 0.01;
+const DEFAULT_DELAY: f32 = 0.1;
+
+// Every frame is decoded up front, only the one shown is uploaded when it changes
+pub struct AnimatedImage {
+    texture: Texture,
+    frames: Vec<(Frame, f32)>, // pixels and delay in seconds
+    current: usize,
+    shown_for: f32,
+    looping: bool,
+    paused: bool,
+    lock_step: bool
+}
+
+impl AnimatedImage {
+    pub fn new(frames: Vec<(Frame, Duration)>) -> Result<AnimatedImage, String> {
+        let (first, _) = frames.first().ok_or("Animation has no frames")?;
+        let texture = Texture::new(first.width as i32, first.height as i32, gl::RGBA8)?;
+        texture.upload_frame(first)?;
+
+        let frames = frames.into_iter().map(|(frame, delay)| {
+            let delay = delay.as_secs_f32();
+            (frame, if delay < MIN_DELAY { DEFAULT_DELAY } else { delay })
+        }).collect();
+
+        Ok(AnimatedImage { texture, frames, current: 0, shown_for: 0.0, looping: true, paused: false, lock_step: false })
+    }
+
+    fn show(&mut self, index: usize) {
+        self.current = index;
+
+        if let Err(e) = self.texture.upload_frame(&self.frames[index].0) {
+            println!("Failed to show animation frame: {}", e);
+        }
+    }
+}
+
+impl FrameSource for AnimatedImage {
+    fn update(&mut self, dt: f32) -> &Texture {
+        if !self.paused && self.lock_step {
+            let last = self.current + 1 == self.frames.len();
+
+            if !last || self.looping {
+                self.show((self.current + 1) % self.frames.len());
+            }
+        } else if !self.paused {
+            self.shown_for += dt;
+            let mut next = self.current;
+
+            // Long frames (or a slow render loop) can pass several animation frames at once
+            while self.shown_for >= self.frames[next].1 {
+                if next + 1 == self.frames.len() && !self.looping {
+                    self.shown_for = 0.0;
+                    break;
+                }
+
+                self.shown_for -= self.frames[next].1;
+                next = (next + 1) % self.frames.len();
+            }
+
+            if next != self.current {
+                self.show(next);
+            }
+        }
+
+        &self.texture
+    }
+
+    fn get_size(&self) -> (i32, i32) {
+        self.texture.get_texture_size()
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        println!("Animation paused: {} (frame {}/{})", self.paused, self.current + 1, self.frames.len());
+    }
+
+    fn step(&mut self) {
+        self.paused = true;
+        self.shown_for = 0.0;
+        self.show((self.current + 1) % self.frames.len());
+        println!("Animation frame {}/{}", self.current + 1, self.frames.len());
+    }
+
+    fn toggle_loop(&mut self) {
+        self.looping = !self.looping;
+        println!("Animation looping: {}", self.looping);
+    }
+
+    fn toggle_lock_step(&mut self) {
+        self.lock_step = !self.lock_step;
+        self.shown_for = 0.0;
+        println!("Animation lock-step: {}", self.lock_step);
+    }
+}
@@ -0,0 +1,114 @@
+pub mod still_image;
+pub mod animated_image;
+pub mod y4m;
+pub mod test_pattern;
+
+use image::AnimationDecoder;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::frame::Frame;
+use crate::texture::Texture;
+
+pub use still_image::StillImage;
+pub use animated_image::AnimatedImage;
+pub use y4m::Y4mVideo;
+pub use test_pattern::{ Pattern, TestPattern };
+
+// Input shown in image mode. Playback controls do nothing for sources that are not animated
+pub trait FrameSource {
+    // Advance by dt seconds and return the texture to draw this frame
+    fn update(&mut self, dt: f32) -> &Texture;
+
+    fn get_size(&self) -> (i32, i32);
+
+    // Scene referred linear values (OpenEXR, Radiance HDR) instead of sRGB encoded ones
+    fn is_linear(&self) -> bool {
+        false
+    }
+
+    fn toggle_pause(&mut self) {}
+
+    // Pauses and shows the next frame
+    fn step(&mut self) {}
+
+    fn toggle_loop(&mut self) {}
+
+    // One frame per rendered frame instead of the timing of the file, for reproducible temporal effects
+    fn toggle_lock_step(&mut self) {}
+}
+
+// Files picked up from playlist directories
+pub fn is_supported(path: &Path) -> bool {
+    image::ImageFormat::from_path(path).is_ok() || is_y4m(path)
+}
+
+pub fn is_y4m(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("y4m"))
+}
+
+// Generated patterns are opened as pattern:<name>. Y4M video and animated GIF, APNG and WebP play all their frames,
+// anything else is a still image
+pub fn open(path: &str) -> Result<Box<dyn FrameSource>, String> {
+    if let Some(name) = path.strip_prefix("pattern:") {
+        let pattern = Pattern::from_name(name).ok_or(format!("Unknown test pattern: {}", name))?;
+        return Ok(Box::new(TestPattern::new(pattern)?));
+    }
+
+    if is_y4m(Path::new(path)) {
+        return Ok(Box::new(Y4mVideo::open(path)?));
+    }
+
+    match decode_animation(path)? {
+        Some(frames) if frames.len() > 1 => Ok(Box::new(AnimatedImage::new(frames)?)),
+        _ => Ok(Box::new(StillImage::load(path)?))
+    }
+}
+
+// Frames composited to the full canvas, with their delays. None when the file is not an animation
+pub fn decode_animation(path: &str) -> Result<Option<Vec<(Frame, Duration)>>, String> {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    let open = || File::open(path).map(BufReader::new).map_err(|e| format!("Failed to open {}: {}", path, e));
+    let decode_error = |e: image::ImageError| format!("Failed to decode {}: {}", path, e);
+
+    let frames = match extension.as_str() {
+        "gif" => GifDecoder::new(open()?).map_err(decode_error)?.into_frames(),
+
+        "png" | "apng" => {
+            let decoder = PngDecoder::new(open()?).map_err(decode_error)?;
+
+            if !decoder.is_apng().map_err(decode_error)? {
+                return Ok(None);
+            }
+
+            decoder.apng().map_err(decode_error)?.into_frames()
+        }
+
+        "webp" => {
+            let decoder = WebPDecoder::new(open()?).map_err(decode_error)?;
+
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+
+            decoder.into_frames()
+        }
+
+        _ => return Ok(None)
+    };
+
+    let frames = frames.collect_frames().map_err(decode_error)?;
+
+    Ok(Some(frames.into_iter().map(|frame| {
+        let delay = Duration::from(frame.delay());
+        let image = frame.into_buffer();
+        let (width, height) = image.dimensions();
+
+        (Frame::new(image.into_raw(), width as usize, height as usize, 4), delay)
+    }).collect()))
+}
@@ -0,0 +1,36 @@
+use image::ImageFormat;
+
+use crate::source::FrameSource;
+use crate::texture::Texture;
+
+pub struct StillImage {
+    texture: Texture,
+    linear: bool
+}
+
+impl StillImage {
+    pub fn load(path: &str) -> Result<StillImage, String> {
+        let texture = Texture::load_file(path)?;
+
+        // Images larger than the window are shown minified
+        texture.generate_mipmaps();
+
+        let linear = matches!(ImageFormat::from_path(path), Ok(ImageFormat::OpenExr | ImageFormat::Hdr));
+
+        Ok(StillImage { texture, linear })
+    }
+}
+
+impl FrameSource for StillImage {
+    fn update(&mut self, _dt: f32) -> &Texture {
+        &self.texture
+    }
+
+    fn get_size(&self) -> (i32, i32) {
+        self.texture.get_texture_size()
+    }
+
+    fn is_linear(&self) -> bool {
+        self.linear
+    }
+}
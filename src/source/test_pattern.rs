@@ -0,0 +1,341 @@
+use crate::frame::Frame;
+use crate::source::FrameSource;
+use crate::texture::Texture;
+
+const WIDTH: usize = 1280;
+const HEIGHT: usize = 720;
+const STEP: f32 = 1.0 / 60.0; // time of one frame in lock-step and single-step
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pattern {
+    SmpteBars,
+    ZonePlate,
+    Checkerboard,
+    Ramps,
+    Text,
+    MovingBox
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 6] = [Pattern::SmpteBars, Pattern::ZonePlate, Pattern::Checkerboard, Pattern::Ramps, Pattern::Text, Pattern::MovingBox];
+
+    // Playlist entries are written as pattern:<name>, e.g. pattern:zone-plate
+    pub fn name(self) -> &'static str {
+        match self {
+            Pattern::SmpteBars => "smpte-bars",
+            Pattern::ZonePlate => "zone-plate",
+            Pattern::Checkerboard => "checkerboard",
+            Pattern::Ramps => "ramps",
+            Pattern::Text => "text",
+            Pattern::MovingBox => "moving-box"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Pattern> {
+        Pattern::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    pub fn is_animated(self) -> bool {
+        self == Pattern::MovingBox
+    }
+
+    // New frame of the pattern, time and frame count only move the moving box
+    pub fn render(self, time: f32, frame_count: u32) -> Frame {
+        let mut frame = Frame::new(vec![0; WIDTH * HEIGHT * 4], WIDTH, HEIGHT, 4);
+        self.draw(&mut frame, time, frame_count);
+
+        frame
+    }
+
+    fn draw(self, frame: &mut Frame, time: f32, frame_count: u32) {
+        match self {
+            Pattern::SmpteBars => smpte_bars(frame),
+            Pattern::ZonePlate => zone_plate(frame),
+            Pattern::Checkerboard => checkerboard(frame),
+            Pattern::Ramps => ramps(frame),
+            Pattern::Text => text(frame),
+            Pattern::MovingBox => moving_box(frame, time, frame_count)
+        }
+    }
+}
+
+// Generated on the CPU, only the moving box is redrawn while it plays
+pub struct TestPattern {
+    pattern: Pattern,
+    texture: Texture,
+    frame: Frame,
+    time: f32,
+    frame_count: u32,
+    paused: bool,
+    lock_step: bool
+}
+
+impl TestPattern {
+    pub fn new(pattern: Pattern) -> Result<TestPattern, String> {
+        let texture = Texture::new(WIDTH as i32, HEIGHT as i32, gl::RGBA8)?;
+        let frame = pattern.render(0.0, 0);
+        texture.upload_frame(&frame)?;
+
+        Ok(TestPattern { pattern, texture, frame, time: 0.0, frame_count: 0, paused: false, lock_step: false })
+    }
+
+    fn advance(&mut self, dt: f32) {
+        self.time += dt;
+        self.frame_count += 1;
+        self.pattern.draw(&mut self.frame, self.time, self.frame_count);
+
+        if let Err(e) = self.texture.upload_frame(&self.frame) {
+            println!("Failed to draw test pattern: {}", e);
+        }
+    }
+}
+
+impl FrameSource for TestPattern {
+    fn update(&mut self, dt: f32) -> &Texture {
+        if self.pattern.is_animated() && !self.paused {
+            self.advance(if self.lock_step { STEP } else { dt });
+        }
+
+        &self.texture
+    }
+
+    fn get_size(&self) -> (i32, i32) {
+        self.texture.get_texture_size()
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        println!("Pattern paused: {} (frame {})", self.paused, self.frame_count);
+    }
+
+    fn step(&mut self) {
+        self.paused = true;
+        self.advance(STEP);
+    }
+
+    fn toggle_lock_step(&mut self) {
+        self.lock_step = !self.lock_step;
+        println!("Pattern lock-step: {}", self.lock_step);
+    }
+}
+
+fn fill_rect(frame: &mut Frame, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+    let (x1, y1) = ((x + w).min(frame.width), (y + h).min(frame.height));
+
+    for row in y.min(y1)..y1 {
+        for px in frame.pixels[(row * frame.width + x.min(x1)) * 4..(row * frame.width + x1) * 4].chunks_exact_mut(4) {
+            px.copy_from_slice(&[color[0], color[1], color[2], 255]);
+        }
+    }
+}
+
+fn put_pixel(frame: &mut Frame, x: usize, y: usize, color: [u8; 3]) {
+    let i = (y * frame.width + x) * 4;
+    frame.pixels[i..i + 4].copy_from_slice(&[color[0], color[1], color[2], 255]);
+}
+
+// SMPTE EG 1 layout: 75% bars, reverse blue bars, then -I, white, +Q, black and the PLUGE
+fn smpte_bars(frame: &mut Frame) {
+    let (w, h) = (frame.width, frame.height);
+
+    let bars = [[191, 191, 191], [191, 191, 0], [0, 191, 191], [0, 191, 0], [191, 0, 191], [191, 0, 0], [0, 0, 191]];
+    let reverse = [[0, 0, 191], [0, 0, 0], [191, 0, 191], [0, 0, 0], [0, 191, 191], [0, 0, 0], [191, 191, 191]];
+    let bar_x = |i: usize| i * w / 7;
+
+    let (top, middle) = (h * 2 / 3, h * 3 / 4);
+
+    for i in 0..7 {
+        fill_rect(frame, bar_x(i), 0, bar_x(i + 1) - bar_x(i), top, bars[i]);
+        fill_rect(frame, bar_x(i), top, bar_x(i + 1) - bar_x(i), middle - top, reverse[i]);
+    }
+
+    // -I, white and +Q each take 5/4 of a bar, the PLUGE (-4%, black, +4%) sits under the red bar
+    let bottom = [(0, w * 5 / 28, [0, 33, 76]), (w * 5 / 28, w * 10 / 28, [255, 255, 255]), (w * 10 / 28, w * 15 / 28, [50, 0, 106]), (w * 15 / 28, bar_x(5), [0, 0, 0])];
+
+    for (x0, x1, color) in bottom {
+        fill_rect(frame, x0, middle, x1 - x0, h - middle, color);
+    }
+
+    let pluge_w = (bar_x(6) - bar_x(5)) / 3;
+    fill_rect(frame, bar_x(5), middle, pluge_w, h - middle, [0, 0, 0]);
+    fill_rect(frame, bar_x(5) + pluge_w, middle, pluge_w, h - middle, [5, 5, 5]);
+    fill_rect(frame, bar_x(5) + 2 * pluge_w, middle, bar_x(6) - bar_x(5) - 2 * pluge_w, h - middle, [10, 10, 10]);
+    fill_rect(frame, bar_x(6), middle, w - bar_x(6), h - middle, [0, 0, 0]);
+}
+
+// Circular zone plate, frequency grows with the distance from the center and reaches Nyquist at the top and bottom edges
+fn zone_plate(frame: &mut Frame) {
+    let (w, h) = (frame.width, frame.height);
+    let k = std::f32::consts::PI / h as f32; // phase = k * r^2, so the frequency k * r / pi is 0.5 cycles per pixel at r = h / 2
+
+    for y in 0..h {
+        for x in 0..w {
+            let (dx, dy) = (x as f32 + 0.5 - w as f32 / 2.0, y as f32 + 0.5 - h as f32 / 2.0);
+            let v = (127.5 + 127.5 * (k * (dx * dx + dy * dy)).cos()).round() as u8;
+
+            put_pixel(frame, x, y, [v, v, v]);
+        }
+    }
+}
+
+// Vertical strips of checkerboards with 1, 2, 3, 4, 8, 16 and 32 pixel cells
+fn checkerboard(frame: &mut Frame) {
+    let sizes = [1, 2, 3, 4, 8, 16, 32];
+    let strip = frame.width / sizes.len();
+
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            let cell = sizes[(x / strip).min(sizes.len() - 1)];
+            let v = if (x / cell + y / cell) % 2 == 0 { 255 } else { 0 };
+
+            put_pixel(frame, x, y, [v, v, v]);
+        }
+    }
+}
+
+// Horizontal bands: smooth gray, red, green and blue ramps, 16 gray steps and a hue sweep
+fn ramps(frame: &mut Frame) {
+    let (w, h) = (frame.width, frame.height);
+    let band = h / 6;
+
+    for y in 0..h {
+        for x in 0..w {
+            let t = x as f32 / (w - 1) as f32;
+            let v = (t * 255.0).round() as u8;
+
+            let color = match (y / band).min(5) {
+                0 => [v, v, v],
+                1 => [v, 0, 0],
+                2 => [0, v, 0],
+                3 => [0, 0, v],
+                4 => {
+                    let step = ((t * 16.0).floor().min(15.0) * 17.0) as u8;
+                    [step, step, step]
+                }
+                _ => hue(t)
+            };
+
+            put_pixel(frame, x, y, color);
+        }
+    }
+}
+
+// Fully saturated color at hue t in 0..1
+fn hue(t: f32) -> [u8; 3] {
+    let channel = |offset: f32| {
+        let k = (t * 6.0 + offset) % 6.0;
+        ((1.0 - (k.min(4.0 - k)).clamp(0.0, 1.0)) * 255.0).round() as u8
+    };
+
+    [channel(5.0), channel(3.0), channel(1.0)]
+}
+
+// The same line at several pixel scales, then colored text that suffers from chroma subsampling
+fn text(frame: &mut Frame) {
+    fill_rect(frame, 0, 0, frame.width, frame.height, [0, 0, 0]);
+
+    let mut y = 8;
+
+    for scale in [1, 2, 3, 4, 6, 8] {
+        draw_text(frame, 8, y, scale, &format!("{}X THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG 0123456789", scale), [255, 255, 255]);
+        y += 9 * scale + 8;
+    }
+
+    let band = frame.height - y;
+    fill_rect(frame, 0, y, frame.width / 2, band, [0, 0, 255]);
+    fill_rect(frame, frame.width / 2, y, frame.width / 2, band, [255, 255, 255]);
+
+    draw_text(frame, 8, y + 8, 2, "RED ON BLUE 0123456789", [255, 0, 0]);
+    draw_text(frame, 8, y + 40, 3, "GREEN ON BLUE", [0, 255, 0]);
+    draw_text(frame, frame.width / 2 + 8, y + 8, 2, "YELLOW ON WHITE 0123456789", [255, 255, 0]);
+    draw_text(frame, frame.width / 2 + 8, y + 40, 3, "BLUE ON WHITE", [0, 0, 255]);
+}
+
+// White box bouncing over a reference grid, with a frame counter for checking lock-step playback
+fn moving_box(frame: &mut Frame, time: f32, frame_count: u32) {
+    let (w, h) = (frame.width, frame.height);
+    let size = 96;
+
+    fill_rect(frame, 0, 0, w, h, [32, 32, 32]);
+
+    for x in (0..w).step_by(64) {
+        fill_rect(frame, x, 0, 1, h, [80, 80, 80]);
+    }
+
+    for y in (0..h).step_by(64) {
+        fill_rect(frame, 0, y, w, 1, [80, 80, 80]);
+    }
+
+    // 320 pixels per second, back and forth
+    let travel = (w - size) as f32;
+    let distance = (time * 320.0) % (2.0 * travel);
+    let x = if distance < travel { distance } else { 2.0 * travel - distance };
+
+    fill_rect(frame, x as usize, (h - size) / 2, size, size, [255, 255, 255]);
+    draw_text(frame, 8, 8, 3, &format!("FRAME {}", frame_count), [255, 255, 255]);
+}
+
+fn draw_text(frame: &mut Frame, x: usize, y: usize, scale: usize, text: &str, color: [u8; 3]) {
+    for (i, c) in text.chars().enumerate() {
+        let Some((_, rows)) = FONT.iter().find(|(glyph, _)| *glyph == c.to_ascii_uppercase()) else {
+            continue;
+        };
+
+        // 5x7 glyphs with one column of spacing
+        let glyph_x = x + i * 6 * scale;
+
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..5 {
+                if bits & (0b10000 >> column) != 0 {
+                    fill_rect(frame, glyph_x + column * scale, y + row * scale, scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+const FONT: [(char, [u8; 7]); 43] = [
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100])
+];
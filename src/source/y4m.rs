@@ -0,0 +1,278 @@
+use std::fs::File;
+use std::io::{ BufRead, BufReader, Read, Seek, SeekFrom };
+
+use crate::frame::Frame;
+use crate::source::FrameSource;
+use crate::texture::Texture;
+
+// Frames behind by more than this (e.g. while the window is dragged) are not caught up on
+const MAX_CATCH_UP: f32 = 1.0;
+
+// Uncompressed YUV4MPEG2 video, 8-bit 4:2:0, 4:2:2, 4:4:4 or mono
+pub struct Y4mReader {
+    reader: BufReader<File>,
+    data_start: u64,
+    pub width: usize,
+    pub height: usize,
+    pub frame_rate: f32,
+    chroma_shift: Option<(usize, usize)>, // horizontal and vertical subsampling as shifts, None = mono
+    full_range: bool,
+    planes: Vec<u8>
+}
+
+impl Y4mReader {
+    pub fn open(path: &str) -> Result<Y4mReader, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let mut reader = BufReader::new(file);
+
+        let header = read_line(&mut reader)?.ok_or("Empty y4m file")?;
+        let mut params = header.split(' ');
+
+        if params.next() != Some("YUV4MPEG2") {
+            return Err(format!("{} is not a y4m file", path));
+        }
+
+        let (mut width, mut height, mut frame_rate) = (0, 0, 25.0);
+        let mut colorspace = "420jpeg";
+        let mut full_range = false;
+
+        // e.g. YUV4MPEG2 W1280 H720 F30000:1001 Ip A1:1 C420jpeg XCOLORRANGE=FULL
+        for param in params.filter(|p| !p.is_empty()) {
+            let (tag, value) = param.split_at(1);
+
+            match tag {
+                "W" => width = value.parse().map_err(|_| format!("Invalid y4m width: {}", value))?,
+                "H" => height = value.parse().map_err(|_| format!("Invalid y4m height: {}", value))?,
+
+                "F" => {
+                    let (numerator, denominator) = value.split_once(':').ok_or(format!("Invalid y4m frame rate: {}", value))?;
+                    let numerator: f32 = numerator.parse().map_err(|_| format!("Invalid y4m frame rate: {}", value))?;
+                    let denominator: f32 = denominator.parse().map_err(|_| format!("Invalid y4m frame rate: {}", value))?;
+
+                    if numerator > 0.0 && denominator > 0.0 {
+                        frame_rate = numerator / denominator;
+                    }
+                }
+
+                "C" => colorspace = value,
+                "X" => full_range |= value == "COLORRANGE=FULL",
+                _ => {}
+            }
+        }
+
+        if width == 0 || height == 0 {
+            return Err(format!("{} has no frame size", path));
+        }
+
+        let chroma_shift = match colorspace {
+            "420jpeg" | "420paldv" | "420mpeg2" | "420" => Some((1, 1)),
+            "422" => Some((1, 0)),
+            "444" => Some((0, 0)),
+            "mono" => None,
+            _ => return Err(format!("Unsupported y4m colorspace: {} (only 8-bit 420, 422, 444 and mono)", colorspace))
+        };
+
+        let data_start = reader.stream_position().map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+        Ok(Y4mReader { reader, data_start, width, height, frame_rate, chroma_shift, full_range, planes: Vec::new() })
+    }
+
+    fn chroma_size(&self) -> (usize, usize) {
+        match self.chroma_shift {
+            Some((sx, sy)) => ((self.width + sx) >> sx, (self.height + sy) >> sy),
+            None => (0, 0)
+        }
+    }
+
+    // Next frame converted to RGBA into rgba. Returns false at the end of the file
+    pub fn read_frame(&mut self, rgba: &mut Frame) -> Result<bool, String> {
+        let Some(header) = read_line(&mut self.reader)? else {
+            return Ok(false);
+        };
+
+        if !header.starts_with("FRAME") {
+            return Err(format!("Invalid y4m frame header: {}", header));
+        }
+
+        let (cw, ch) = self.chroma_size();
+        self.planes.resize(self.width * self.height + 2 * cw * ch, 0);
+        self.reader.read_exact(&mut self.planes).map_err(|e| format!("Truncated y4m frame: {}", e))?;
+
+        self.convert(rgba);
+
+        Ok(true)
+    }
+
+    pub fn rewind(&mut self) -> Result<(), String> {
+        self.reader.seek(SeekFrom::Start(self.data_start)).map(|_| ()).map_err(|e| format!("Failed to rewind y4m: {}", e))
+    }
+
+    // BT.601, limited range unless the file says otherwise
+    fn convert(&self, rgba: &mut Frame) {
+        let (w, h) = (self.width, self.height);
+        let (cw, ch) = self.chroma_size();
+        let (sx, sy) = self.chroma_shift.unwrap_or((0, 0));
+
+        let (y_plane, chroma) = self.planes.split_at(w * h);
+        let (cb_plane, cr_plane) = chroma.split_at(cw * ch);
+
+        let (y_offset, y_scale, c_scale) = match self.full_range {
+            true => (0.0, 1.0, 1.0),
+            false => (16.0, 255.0 / 219.0, 255.0 / 224.0)
+        };
+
+        rgba.pixels.resize(w * h * 4, 0);
+        rgba.width = w;
+        rgba.height = h;
+        rgba.channels = 4;
+
+        for (y, row) in rgba.pixels.chunks_exact_mut(w * 4).enumerate() {
+            for (x, px) in row.chunks_exact_mut(4).enumerate() {
+                let luma = (y_plane[y * w + x] as f32 - y_offset) * y_scale;
+
+                let (cb, cr) = match self.chroma_shift {
+                    Some(_) => {
+                        let i = (y >> sy) * cw + (x >> sx);
+                        ((cb_plane[i] as f32 - 128.0) * c_scale, (cr_plane[i] as f32 - 128.0) * c_scale)
+                    }
+
+                    None => (0.0, 0.0)
+                };
+
+                px[0] = (luma + 1.402 * cr).round().clamp(0.0, 255.0) as u8;
+                px[1] = (luma - 0.344136 * cb - 0.714136 * cr).round().clamp(0.0, 255.0) as u8;
+                px[2] = (luma + 1.772 * cb).round().clamp(0.0, 255.0) as u8;
+                px[3] = 255;
+            }
+        }
+    }
+}
+
+// Header lines end with a newline, None at the end of the file
+fn read_line(reader: &mut BufReader<File>) -> Result<Option<String>, String> {
+    let mut line = Vec::new();
+    reader.by_ref().take(4096).read_until(b'\n', &mut line).map_err(|e| format!("Failed to read y4m: {}", e))?;
+
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    if line.pop() != Some(b'\n') {
+        return Err("Invalid y4m header line".to_string());
+    }
+
+    String::from_utf8(line).map(Some).map_err(|_| "Invalid y4m header line".to_string())
+}
+
+// Streams frames from the file at its frame rate, or one frame per rendered frame in lock-step
+pub struct Y4mVideo {
+    reader: Y4mReader,
+    texture: Texture,
+    rgba: Frame,
+    frame: usize,
+    shown_for: f32,
+    looping: bool,
+    paused: bool,
+    lock_step: bool
+}
+
+impl Y4mVideo {
+    pub fn open(path: &str) -> Result<Y4mVideo, String> {
+        let mut reader = Y4mReader::open(path)?;
+        let mut rgba = Frame::new(Vec::new(), 0, 0, 4);
+
+        if !reader.read_frame(&mut rgba)? {
+            return Err(format!("{} has no frames", path));
+        }
+
+        let texture = Texture::new(reader.width as i32, reader.height as i32, gl::RGBA8)?;
+        texture.upload_frame(&rgba)?;
+
+        println!("Y4M {}x{} at {:.3} fps", reader.width, reader.height, reader.frame_rate);
+
+        Ok(Y4mVideo { reader, texture, rgba, frame: 0, shown_for: 0.0, looping: true, paused: false, lock_step: false })
+    }
+
+    // Reads frames until count are passed, the last one is uploaded
+    fn advance(&mut self, count: usize) {
+        let mut read = false;
+
+        for _ in 0..count {
+            let result = match self.reader.read_frame(&mut self.rgba) {
+                Ok(false) if self.looping => {
+                    self.frame = 0;
+                    self.reader.rewind().and_then(|_| self.reader.read_frame(&mut self.rgba))
+                }
+
+                Ok(available) => {
+                    self.frame += available as usize;
+                    Ok(available)
+                }
+
+                Err(e) => Err(e)
+            };
+
+            match result {
+                Ok(true) => read = true,
+
+                // Stays on the last frame
+                Ok(false) => break,
+
+                Err(e) => {
+                    println!("Y4M playback stopped: {}", e);
+                    self.paused = true;
+                    break;
+                }
+            }
+        }
+
+        if read && let Err(e) = self.texture.upload_frame(&self.rgba) {
+            println!("Y4M playback stopped: {}", e);
+            self.paused = true;
+        }
+    }
+}
+
+impl FrameSource for Y4mVideo {
+    fn update(&mut self, dt: f32) -> &Texture {
+        if !self.paused {
+            if self.lock_step {
+                self.advance(1);
+            } else {
+                let frame_duration = 1.0 / self.reader.frame_rate;
+                self.shown_for = (self.shown_for + dt).min(MAX_CATCH_UP);
+
+                let count = (self.shown_for / frame_duration) as usize;
+                self.shown_for -= count as f32 * frame_duration;
+                self.advance(count);
+            }
+        }
+
+        &self.texture
+    }
+
+    fn get_size(&self) -> (i32, i32) {
+        self.texture.get_texture_size()
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        println!("Y4M paused: {} (frame {})", self.paused, self.frame + 1);
+    }
+
+    fn step(&mut self) {
+        self.paused = true;
+        self.advance(1);
+        println!("Y4M frame {}", self.frame + 1);
+    }
+
+    fn toggle_loop(&mut self) {
+        self.looping = !self.looping;
+        println!("Y4M looping: {}", self.looping);
+    }
+
+    fn toggle_lock_step(&mut self) {
+        self.lock_step = !self.lock_step;
+        println!("Y4M lock-step: {}", self.lock_step);
+    }
+}
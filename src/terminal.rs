@@ -0,0 +1,337 @@
+use scrap::{ Capturer, Display };
+use std::io::ErrorKind::WouldBlock;
+use std::io::Write;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::ascii_export::{ AsciiGrid, AsciiSettings, convert, to_ansi };
+use crate::effect_params::ParamSet;
+use crate::frame::Frame;
+use crate::help_functions::{ DEFAULT_GLYPH_CHARS, effect_params };
+use crate::playlist::Playlist;
+use crate::select_shader::SelectShader;
+use crate::source::y4m::Y4mReader;
+use crate::source::{ Pattern, decode_animation, is_y4m };
+
+const FRAME_TIME: Duration = Duration::from_millis(66); // about 15 fps, the terminal is the bottleneck
+
+// Terminal characters are about twice as tall as wide, each covers this much of a virtual window
+const CELL_SIZE: (f32, f32) = (8.0, 16.0);
+
+static RESIZED: AtomicBool = AtomicBool::new(false);
+static QUIT: AtomicBool = AtomicBool::new(false);
+
+// Frames on the CPU, the window sources need a GL context
+enum Input {
+    Still,
+    Animation(Vec<(Frame, Duration)>, Duration, usize), // frames, total duration and the frame shown
+    Video(Y4mReader, u64, Option<u64>), // frame shown and the frame count once the end was reached
+    Pattern(Pattern),
+    Capture(Capturer)
+}
+
+struct TerminalSource {
+    input: Input,
+    frame: Frame
+}
+
+impl TerminalSource {
+    // Same kinds of files as the image mode, shown from the first frame
+    fn open(path: &str) -> Result<TerminalSource, String> {
+        if let Some(name) = path.strip_prefix("pattern:") {
+            let pattern = Pattern::from_name(name).ok_or(format!("Unknown test pattern: {}", name))?;
+            return Ok(TerminalSource { input: Input::Pattern(pattern), frame: pattern.render(0.0, 0) });
+        }
+
+        if is_y4m(std::path::Path::new(path)) {
+            let mut reader = Y4mReader::open(path)?;
+            let mut frame = Frame::new(vec![0; reader.width * reader.height * 4], reader.width, reader.height, 4);
+            reader.read_frame(&mut frame)?;
+
+            return Ok(TerminalSource { input: Input::Video(reader, 0, None), frame });
+        }
+
+        if let Some(frames) = decode_animation(path)?.filter(|frames| frames.len() > 1) {
+            let total = frames.iter().map(|(_, delay)| *delay).sum::<Duration>().max(Duration::from_millis(10));
+            let frame = frames[0].0.clone();
+
+            return Ok(TerminalSource { input: Input::Animation(frames, total, 0), frame });
+        }
+
+        let image = image::open(path).map_err(|e| format!("Failed to load {}: {}", path, e))?.to_rgba8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+
+        Ok(TerminalSource { input: Input::Still, frame: Frame::new(image.into_raw(), width, height, 4) })
+    }
+
+    fn capture() -> Result<TerminalSource, String> {
+        let display = Display::primary().map_err(|e| format!("Failed to get primary display: {}", e))?;
+        let frame = Frame::new(vec![0; display.width() * display.height() * 4], display.width(), display.height(), 4);
+        let capturer = Capturer::new(display).map_err(|e| format!("Failed to create capturer: {}", e))?;
+
+        Ok(TerminalSource { input: Input::Capture(capturer), frame })
+    }
+
+    // Move to the given playback time, returns true when the frame changed
+    fn update(&mut self, time: Duration) -> Result<bool, String> {
+        match &mut self.input {
+            Input::Still => Ok(false),
+
+            Input::Animation(frames, total, shown) => {
+                let mut t = Duration::from_nanos((time.as_nanos() % total.as_nanos()) as u64);
+                let index = frames.iter().position(|(_, delay)| t.checked_sub(*delay).map(|rest| t = rest).is_none()).unwrap_or(0);
+
+                if index == *shown {
+                    return Ok(false);
+                }
+
+                *shown = index;
+                self.frame = frames[index].0.clone();
+                Ok(true)
+            }
+
+            Input::Video(reader, shown, length) => {
+                let mut target = (time.as_secs_f64() * reader.frame_rate as f64) as u64;
+
+                if let Some(length) = *length {
+                    target %= length;
+                }
+
+                if *shown == target {
+                    return Ok(false);
+                }
+
+                // Frames behind the clock are read over, only the last one is converted
+                while *shown != target {
+                    if *shown > target || !reader.read_frame(&mut self.frame)? {
+                        // Past the end the clock wraps around the video
+                        if *shown < target {
+                            *length = Some(*shown + 1);
+                            target %= *shown + 1;
+                        }
+
+                        reader.rewind()?;
+                        reader.read_frame(&mut self.frame)?;
+                        *shown = 0;
+                        continue;
+                    }
+
+                    *shown += 1;
+                }
+
+                Ok(true)
+            }
+
+            Input::Pattern(pattern) if pattern.is_animated() => {
+                self.frame = pattern.render(time.as_secs_f32(), (time.as_secs_f32() * 60.0) as u32);
+                Ok(true)
+            }
+
+            Input::Pattern(_) => Ok(false),
+
+            Input::Capture(capturer) => match capturer.frame() {
+                Ok(bgra) => {
+                    let (width, height) = (self.frame.width, self.frame.height);
+                    let stride = bgra.len() / height;
+
+                    for (y, row) in self.frame.pixels.chunks_exact_mut(width * 4).enumerate() {
+                        for (dst, src) in row.chunks_exact_mut(4).zip(bgra[y * stride..].chunks_exact(4)) {
+                            dst.copy_from_slice(&[src[2], src[1], src[0], 255]);
+                        }
+                    }
+
+                    Ok(true)
+                }
+
+                Err(ref e) if e.kind() == WouldBlock => Ok(false),
+                Err(e) => Err(format!("Capture error: {}", e))
+            }
+        }
+    }
+}
+
+// Renders the ASCII effect into the console with 24-bit colors until Ctrl+C. Shows the screen capture or the first readable playlist entry
+pub fn run(specs: &[String], capture: bool) -> Result<(), String> {
+    let mut source = if capture {
+        TerminalSource::capture()?
+    } else {
+        let playlist = Playlist::from_specs(specs)?;
+
+        playlist.entries().iter()
+            .find_map(|path| TerminalSource::open(&path.to_string_lossy()).map_err(|e| println!("Skipping {}", e)).ok())
+            .ok_or("No readable image in the playlist")?
+    };
+
+    let chars: Vec<char> = DEFAULT_GLYPH_CHARS.chars().collect();
+
+    // Default parameters of the ASCII effect
+    let mut params = ParamSet::new();
+    params.set_group("effect", effect_params(&SelectShader::Ascii));
+    let settings = AsciiSettings::from_params(CELL_SIZE, &params);
+
+    listen_for_signals();
+    enable_ansi_output();
+
+    // Alternate screen without cursor, restored on exit
+    let mut stdout = std::io::stdout();
+    print!("\x1b[?1049h\x1b[?25l");
+
+    let start = Instant::now();
+    let mut last_size = None;
+    let mut last_grid: Option<AsciiGrid> = None; // edges of the last frame, for hysteresis
+    let mut result = Ok(());
+
+    while !QUIT.load(Ordering::Relaxed) {
+        let size = terminal_size();
+        let resized = RESIZED.swap(false, Ordering::Relaxed) || last_size != Some(size);
+
+        let changed = match source.update(start.elapsed()) {
+            Ok(changed) => changed,
+
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+
+        if resized || changed {
+            let (columns, rows) = fit(source.frame.width, source.frame.height, size);
+            let resolution = ((columns as f32 * CELL_SIZE.0) as i32, (rows as f32 * CELL_SIZE.1) as i32);
+            let grid = convert(&source.frame, resolution, &settings, chars.len() as i32, last_grid.as_ref());
+
+            let clear = if resized { "\x1b[2J" } else { "" };
+            let _ = write!(stdout, "{}\x1b[H{}", clear, to_ansi(&grid, &chars));
+            let _ = stdout.flush();
+
+            last_size = Some(size);
+            last_grid = Some(grid);
+        }
+
+        std::thread::sleep(FRAME_TIME);
+    }
+
+    print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+    let _ = stdout.flush();
+
+    result
+}
+
+// Largest cell grid with the aspect ratio of the frame, one row is left free so the last line doesn't scroll
+fn fit(width: usize, height: usize, terminal: (usize, usize)) -> (usize, usize) {
+    let (columns, rows) = (terminal.0.max(1), terminal.1.saturating_sub(1).max(1));
+    let cell_aspect = CELL_SIZE.1 / CELL_SIZE.0;
+    let aspect = width as f32 / height as f32;
+
+    let fit_rows = ((columns as f32 / aspect / cell_aspect) as usize).clamp(1, rows);
+    let fit_columns = ((fit_rows as f32 * aspect * cell_aspect) as usize).clamp(1, columns);
+
+    (fit_columns, fit_rows)
+}
+
+#[cfg(unix)]
+fn terminal_size() -> (usize, usize) {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 && size.ws_row > 0 {
+        return (size.ws_col as usize, size.ws_row as usize);
+    }
+
+    size_from_env()
+}
+
+// Visible window of the console, not its scrollback buffer
+#[cfg(target_os = "windows")]
+fn terminal_size() -> (usize, usize) {
+    use windows::Win32::System::Console::{ CONSOLE_SCREEN_BUFFER_INFO, GetConsoleScreenBufferInfo, GetStdHandle, STD_OUTPUT_HANDLE };
+
+    let mut info = CONSOLE_SCREEN_BUFFER_INFO::default();
+
+    let ok = unsafe {
+        match GetStdHandle(STD_OUTPUT_HANDLE) {
+            Ok(handle) => GetConsoleScreenBufferInfo(handle, &mut info).is_ok(),
+            Err(_) => false
+        }
+    };
+
+    let window = info.srWindow;
+    let (columns, rows) = (window.Right - window.Left + 1, window.Bottom - window.Top + 1);
+
+    if ok && columns > 0 && rows > 0 {
+        return (columns as usize, rows as usize);
+    }
+
+    size_from_env()
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn terminal_size() -> (usize, usize) {
+    size_from_env()
+}
+
+fn size_from_env() -> (usize, usize) {
+    let var = |name: &str, default: usize| std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+
+    (var("COLUMNS", 80), var("LINES", 24))
+}
+
+// Conhost only interprets the escape sequences with virtual terminal processing on
+#[cfg(target_os = "windows")]
+fn enable_ansi_output() {
+    use windows::Win32::System::Console::{ CONSOLE_MODE, ENABLE_VIRTUAL_TERMINAL_PROCESSING, GetConsoleMode, GetStdHandle, STD_OUTPUT_HANDLE, SetConsoleMode };
+
+    let mut mode = CONSOLE_MODE::default();
+
+    let result = unsafe {
+        GetStdHandle(STD_OUTPUT_HANDLE)
+            .and_then(|handle| GetConsoleMode(handle, &mut mode).map(|_| handle))
+            .and_then(|handle| SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING))
+    };
+
+    if let Err(e) = result {
+        println!("Failed to enable ANSI escapes in the console: {}", e);
+    }
+}
+
+// Terminals elsewhere understand the escape sequences as they are
+#[cfg(not(target_os = "windows"))]
+fn enable_ansi_output() {}
+
+// SIGWINCH redraws at the new size, SIGINT and SIGTERM leave the alternate screen before exiting
+#[cfg(unix)]
+fn listen_for_signals() {
+    extern "C" fn on_resize(_: libc::c_int) {
+        RESIZED.store(true, Ordering::Relaxed);
+    }
+
+    extern "C" fn on_quit(_: libc::c_int) {
+        QUIT.store(true, Ordering::Relaxed);
+    }
+
+    unsafe {
+        libc::signal(libc::SIGWINCH, on_resize as *const () as libc::sighandler_t);
+        libc::signal(libc::SIGINT, on_quit as *const () as libc::sighandler_t);
+        libc::signal(libc::SIGTERM, on_quit as *const () as libc::sighandler_t);
+    }
+}
+
+// Ctrl+C, Ctrl+Break and closing the console leave the alternate screen before exiting, size changes are picked up by polling
+#[cfg(target_os = "windows")]
+fn listen_for_signals() {
+    use windows::Win32::System::Console::SetConsoleCtrlHandler;
+    use windows::core::BOOL;
+
+    unsafe extern "system" fn on_quit(_: u32) -> BOOL {
+        QUIT.store(true, Ordering::Relaxed);
+        true.into()
+    }
+
+    unsafe {
+        if let Err(e) = SetConsoleCtrlHandler(Some(on_quit), true) {
+            println!("Failed to install the console handler, the terminal is not restored on Ctrl+C: {}", e);
+        }
+    }
+}
+
+// Other platforms have neither, the terminal is not restored when killed
+#[cfg(not(any(unix, target_os = "windows")))]
+fn listen_for_signals() {}
@@ -1,34 +1,174 @@
 use axum::{
     extract::ws::{ WebSocketUpgrade, WebSocket, Message },
-    response::IntoResponse,
+    body::{ Body, Bytes },
+    http::{ header, StatusCode },
+    response::{ IntoResponse, Response },
     routing::get,
     Router
 };
-use tokio::{ sync::broadcast, net::TcpListener, runtime::Runtime };
+use futures_util::stream;
+use std::{ convert::Infallible, sync::Arc, time::{ Duration, Instant } };
+use tokio::{ sync::broadcast::error::RecvError, net::TcpListener, runtime::Runtime, time::timeout };
 
-async fn ws_handler(ws: WebSocketUpgrade, tx: broadcast::Sender<Vec<u8>>) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, tx.subscribe()))
+use crate::adaptive_quality::AdaptiveQuality;
+use crate::encoder::ShareFormat;
+use crate::share_hub::ShareHub;
+
+const MJPEG_BOUNDARY: &str = "frame";
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(2);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+
+async fn ws_handler(ws: WebSocketUpgrade, hub: Arc<ShareHub>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, hub))
+}
+
+async fn handshake(socket: &mut WebSocket) -> Result<ShareFormat, String> {
+    // Viewers that do not send a format in time get the default jpeg stream
+    match timeout(HANDSHAKE_TIMEOUT, socket.recv()).await {
+        Ok(Some(Ok(Message::Text(message)))) => ShareFormat::parse(message.as_str()),
+        Ok(Some(Ok(_))) | Err(_) => Ok(ShareFormat::default()),
+        Ok(Some(Err(e))) => Err(format!("Handshake failed: {}", e)),
+        Ok(None) => Err("Viewer disconnected during handshake".to_string())
+    }
+}
+
+async fn handle_socket(mut socket: WebSocket, hub: Arc<ShareHub>) {
+    let format = match handshake(&mut socket).await {
+        Ok(format) => format,
+        Err(e) => {
+            println!("Share viewer rejected: {}", e);
+            let _ = socket.send(Message::Text(format!("error: {}", e).into())).await;
+            return;
+        }
+    };
+
+    // Tell the viewer which mime type the binary frames have
+    if socket.send(Message::Text(format.mime_type().into())).await.is_err() {
+        return;
+    }
+
+    let mut quality = AdaptiveQuality::new("WebSocket viewer", format);
+    let mut rx = hub.subscribe(quality.stream());
+
+    loop {
+        match rx.recv().await {
+            Ok(frame) => {
+                let start = Instant::now();
+                let bytes = frame.len();
+
+                if socket.send(Message::Binary(frame)).await.is_err() {
+                    break;
+                }
+
+                quality.record_frame(bytes, rx.len(), start.elapsed());
+            },
+
+            // Slow viewer, skip the missed frames and lower the quality instead of disconnecting
+            Err(RecvError::Lagged(skipped)) => quality.record_lag(skipped),
+            Err(RecvError::Closed) => break
+        }
+
+        if quality.evaluate() {
+            rx = hub.subscribe(quality.stream());
+        }
+    }
 }
 
-async fn handle_socket(mut socket: WebSocket, mut rx: broadcast::Receiver<Vec<u8>>) {
-    while let Ok(frame) = rx.recv().await {
-        if socket.send(Message::Binary(frame.into())).await.is_err() {
-            break;
+async fn mjpeg_handler(hub: Arc<ShareHub>) -> impl IntoResponse {
+    let quality = AdaptiveQuality::new("MJPEG viewer", ShareFormat::default());
+    let rx = hub.subscribe(quality.stream());
+
+    // Every JPEG is sent as its own part, the consumer replaces the previous image with the next one
+    // Time between handing out a part and the next poll is the time the consumer needed to take it
+    let frames = stream::unfold((hub, rx, quality, None), |(hub, mut rx, mut quality, mut sent)| async move {
+        // Taken before waiting for the next frame, which is not the consumer's time
+        let polled = Instant::now();
+
+        loop {
+            match rx.recv().await {
+                Ok(frame) => {
+                    if let Some((start, bytes)) = sent.take() {
+                        quality.record_frame(bytes, rx.len(), polled.duration_since(start));
+                    }
+
+                    if quality.evaluate() {
+                        rx = hub.subscribe(quality.stream());
+                    }
+
+                    let part_header = format!("--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n", MJPEG_BOUNDARY, frame.len());
+
+                    let mut part = Vec::with_capacity(part_header.len() + frame.len() + 2);
+                    part.extend_from_slice(part_header.as_bytes());
+                    part.extend_from_slice(&frame);
+                    part.extend_from_slice(b"\r\n");
+
+                    sent = Some((Instant::now(), part.len()));
+
+                    return Some((Ok::<Bytes, Infallible>(Bytes::from(part)), (hub, rx, quality, sent)));
+                },
+
+                // Slow consumer, skip the missed frames and continue with the newest one
+                Err(RecvError::Lagged(skipped)) => quality.record_lag(skipped),
+                Err(RecvError::Closed) => return None
+            }
+        }
+    });
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, format!("multipart/x-mixed-replace; boundary={}", MJPEG_BOUNDARY))
+        .header(header::CACHE_CONTROL, "no-cache, no-store")
+        .body(Body::from_stream(frames))
+        .unwrap()
+}
+
+async fn snapshot_handler(hub: Arc<ShareHub>) -> Response {
+    let jpeg_response = |frame: Bytes| ([(header::CONTENT_TYPE, "image/jpeg"), (header::CACHE_CONTROL, "no-cache, no-store")], frame).into_response();
+
+    if let Some(frame) = hub.last_jpeg() {
+        return jpeg_response(frame);
+    }
+
+    // Nothing published recently, subscribing makes the render loop produce a frame
+    let mut rx = hub.subscribe(ShareHub::snapshot_stream());
+
+    loop {
+        match timeout(SNAPSHOT_TIMEOUT, rx.recv()).await {
+            Ok(Ok(frame)) => return jpeg_response(frame),
+
+            Ok(Err(RecvError::Lagged(_))) => continue,
+            Ok(Err(RecvError::Closed)) | Err(_) => {
+                return (StatusCode::SERVICE_UNAVAILABLE, "No frame available, is window sharing turned on?").into_response();
+            }
         }
     }
 }
 
-pub fn start_server(tx: broadcast::Sender<Vec<u8>>) {
+pub fn start_server(hub: Arc<ShareHub>) {
     std::thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         rt.block_on(async move {
-            let app = Router::new().route(
-                "/ws",
-                get({
-                    let tx = tx.clone();
-                    move |ws| ws_handler(ws, tx.clone())
-                })
-            );
+            let app = Router::new()
+                .route(
+                    "/ws",
+                    get({
+                        let hub = hub.clone();
+                        move |ws| ws_handler(ws, hub.clone())
+                    })
+                )
+                .route(
+                    "/stream.mjpg",
+                    get({
+                        let hub = hub.clone();
+                        move || mjpeg_handler(hub.clone())
+                    })
+                )
+                .route(
+                    "/snapshot.jpg",
+                    get({
+                        let hub = hub.clone();
+                        move || snapshot_handler(hub.clone())
+                    })
+                );
 
             let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
 